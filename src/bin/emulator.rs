@@ -0,0 +1,136 @@
+use std::io::{Write, stdout};
+
+use anyhow::Result;
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Attribute, Print, SetAttribute},
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use futures::StreamExt;
+use nalgebra::Vector2;
+use tokio::{
+    io::{self, AsyncReadExt, AsyncWriteExt, DuplexStream},
+    select,
+};
+
+use model_100_serial::{
+    emulator::{self, Emulator},
+    modules::menu::Menu,
+    screen::Screen,
+    state::State,
+};
+
+/// Runs the bridge against an emulated Model 100 in the host terminal.
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<()> {
+    let (host, device) = io::duplex(4096);
+    let mut screen = State::new(Box::new(host)).await?;
+    let mut menu = Menu::default();
+
+    terminal::enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen, Hide)?;
+
+    let result = select! {
+        result = model_100_serial::run(&mut screen, &mut menu) => result,
+        result = emulate(device) => result,
+    };
+
+    execute!(stdout(), LeaveAlternateScreen, Show)?;
+    terminal::disable_raw_mode()?;
+    result
+}
+
+async fn emulate(device: DuplexStream) -> Result<()> {
+    let (mut rx, mut tx) = io::split(device);
+    let mut emulator = Emulator::new();
+    let mut events = EventStream::new();
+    let mut buf = [0; 1024];
+
+    render(&emulator)?;
+    loop {
+        select! {
+            read = rx.read(&mut buf) => {
+                let read = read?;
+                if read == 0 {
+                    return Ok(());
+                }
+
+                emulator.feed(&buf[..read]);
+                render(&emulator)?;
+            }
+            event = events.next() => {
+                let Some(event) = event else { return Ok(()) };
+                match event? {
+                    Event::Key(KeyEvent { code: KeyCode::F(10), .. }) => return Ok(()),
+                    Event::Key(key) if key.kind != KeyEventKind::Release => {
+                        if let Some(byte) = key_code(key) {
+                            tx.write_u8(byte).await?;
+                        }
+                    }
+                    Event::Resize(..) => render(&emulator)?,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+fn render(emulator: &Emulator) -> Result<()> {
+    let mut out = stdout();
+    let border = "─".repeat(Screen::WIDTH);
+    queue!(
+        out,
+        Clear(ClearType::All),
+        MoveTo(0, 0),
+        Print(format!("┌{border}┐")),
+        MoveTo(0, Screen::HEIGHT as u16 + 1),
+        Print(format!("└{border}┘")),
+        MoveTo(0, Screen::HEIGHT as u16 + 2),
+        Print("F10 to quit"),
+    )?;
+
+    for y in 0..Screen::HEIGHT {
+        queue!(out, MoveTo(0, y as u16 + 1), Print('│'))?;
+        for x in 0..Screen::WIDTH {
+            let pos = Vector2::new(x, y);
+            let chr = emulator.get(pos).unwrap();
+            let cursor = emulator.cursor_visible() && emulator.cursor() == pos;
+
+            if chr.inverted() ^ cursor {
+                queue!(out, SetAttribute(Attribute::Reverse))?;
+            }
+            queue!(
+                out,
+                Print(emulator::glyph(chr.char())),
+                SetAttribute(Attribute::Reset)
+            )?;
+        }
+        queue!(out, Print('│'))?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+/// Translates a host key press into the byte a Model 100 would send.
+fn key_code(key: KeyEvent) -> Option<u8> {
+    Some(match key.code {
+        KeyCode::Char(chr) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let chr = chr.to_ascii_lowercase();
+            chr.is_ascii_lowercase().then(|| chr as u8 - b'a' + 1)?
+        }
+        KeyCode::Char(chr) if chr.is_ascii() => chr as u8,
+        KeyCode::Enter => 0x0D,
+        KeyCode::Esc => 0x1B,
+        KeyCode::Backspace => 0x08,
+        KeyCode::Delete => 0x7F,
+        KeyCode::Tab => 0x09,
+        KeyCode::Left => 0x1D,
+        KeyCode::Right => 0x1C,
+        KeyCode::Up => 0x1E,
+        KeyCode::Down => 0x1F,
+        _ => return None,
+    })
+}
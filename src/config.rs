@@ -0,0 +1,297 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, de::DeserializeOwned};
+use tokio_serial::{DataBits, SerialPortBuilder, StopBits};
+
+use crate::keymap::{Action, Keymap};
+
+#[derive(Parser)]
+#[command(about = "Serial bridge for the TRS-80 Model 100")]
+pub struct Args {
+    /// TOML config file, values from the command line take priority.
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// Print the TELCOM STAT string matching the serial settings and exit.
+    #[arg(long)]
+    pub stat: bool,
+
+    /// Log level or filter, like `debug` or `info,model_100_serial=trace`.
+    /// Serial traffic is logged at `trace`.
+    #[arg(long)]
+    pub log_level: Option<String>,
+
+    #[command(flatten)]
+    pub serial: SerialArgs,
+}
+
+#[derive(clap::Args)]
+pub struct SerialArgs {
+    /// Serial device to open, or `tcp://host:port`, `unix:/path` or `pty`.
+    #[arg(short, long)]
+    pub port: Option<String>,
+    /// Baud rate, the Model 100 supports 75 through 19200.
+    #[arg(short, long)]
+    pub baud: Option<u32>,
+    #[arg(long, value_enum)]
+    pub flow_control: Option<FlowControl>,
+    /// Word length, the Model 100 supports 6, 7 or 8.
+    #[arg(long)]
+    pub data_bits: Option<u8>,
+    #[arg(long, value_enum)]
+    pub parity: Option<Parity>,
+    #[arg(long)]
+    pub stop_bits: Option<u8>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub serial: SerialConfig,
+    pub log: LogConfig,
+    pub keymap: Keymap,
+    /// Menu entries in order, written as `[[module]]` tables. Every
+    /// registered module is shown when there are none.
+    #[serde(rename = "module")]
+    pub modules: Vec<ModuleConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub level: String,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SerialConfig {
+    pub port: String,
+    pub baud: u32,
+    pub flow_control: FlowControl,
+    pub data_bits: u8,
+    pub parity: Parity,
+    pub stop_bits: u8,
+}
+
+#[derive(Clone, Copy, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FlowControl {
+    None,
+    /// XON/XOFF
+    Software,
+    /// RTS/CTS
+    Hardware,
+}
+
+#[derive(Clone, Copy, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Parity {
+    None,
+    Odd,
+    Even,
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModuleConfig {
+    /// Which module to run, like `chatgpt`.
+    pub kind: String,
+    /// Shown in the menu instead of the module's own name.
+    /// Needed to list the same kind more than once.
+    pub name: Option<String>,
+    /// Leaves the module out of the menu without removing its settings.
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+    /// Handed to the module when it's opened, see each module for its options.
+    #[serde(default)]
+    pub settings: toml::Table,
+    /// Global shortcuts sent to the module instead while it's on screen, like
+    /// `["switch"]` for a module that uses CTRL+N itself.
+    #[serde(default)]
+    pub passthrough: Vec<Action>,
+}
+
+impl Config {
+    pub fn load(args: &Args) -> Result<Self> {
+        let mut config = match &args.config {
+            Some(path) => Self::read(path)?,
+            None => Config::default(),
+        };
+
+        config.serial.apply(&args.serial);
+        if let Some(level) = &args.log_level {
+            config.log.level = level.clone();
+        }
+        Ok(config)
+    }
+
+    /// Reads a config file without applying command line overrides.
+    pub fn read(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config `{}`", path.display()))?;
+        toml::from_str(&raw).with_context(|| format!("Failed to parse config `{}`", path.display()))
+    }
+}
+
+impl ModuleConfig {
+    pub fn new(kind: &str) -> Self {
+        Self {
+            kind: kind.into(),
+            name: None,
+            enabled: true,
+            settings: toml::Table::new(),
+            passthrough: Vec::new(),
+        }
+    }
+
+    /// Reads the settings table into a module's own settings type.
+    pub fn settings<T: DeserializeOwned>(&self) -> Result<T> {
+        self.settings
+            .clone()
+            .try_into()
+            .with_context(|| format!("Invalid settings for module `{}`", self.kind))
+    }
+}
+
+impl SerialConfig {
+    fn apply(&mut self, args: &SerialArgs) {
+        let SerialArgs {
+            port,
+            baud,
+            flow_control,
+            data_bits,
+            parity,
+            stop_bits,
+        } = args;
+
+        if let Some(port) = port {
+            self.port = port.clone();
+        }
+
+        self.baud = baud.unwrap_or(self.baud);
+        self.flow_control = flow_control.unwrap_or(self.flow_control);
+        self.data_bits = data_bits.unwrap_or(self.data_bits);
+        self.parity = parity.unwrap_or(self.parity);
+        self.stop_bits = stop_bits.unwrap_or(self.stop_bits);
+    }
+
+    pub fn builder(&self) -> Result<SerialPortBuilder> {
+        let data_bits = match self.data_bits {
+            5 => DataBits::Five,
+            6 => DataBits::Six,
+            7 => DataBits::Seven,
+            8 => DataBits::Eight,
+            x => bail!("Unsupported data bits: {x}"),
+        };
+
+        let stop_bits = match self.stop_bits {
+            1 => StopBits::One,
+            2 => StopBits::Two,
+            x => bail!("Unsupported stop bits: {x}"),
+        };
+
+        Ok(tokio_serial::new(&self.port, self.baud)
+            .data_bits(data_bits)
+            .stop_bits(stop_bits)
+            .parity(self.parity.into())
+            .flow_control(self.flow_control.into()))
+    }
+
+    /// Builds the string to enter in TELCOM's `STAT` to match these settings,
+    /// in the form `<baud><word length><parity><stop bits><xon/xoff>`.
+    pub fn stat(&self) -> Result<String> {
+        let baud = match self.baud {
+            75 => '1',
+            110 => '2',
+            300 => '3',
+            600 => '4',
+            1200 => '5',
+            2400 => '6',
+            4800 => '7',
+            9600 => '8',
+            19200 => '9',
+            x => bail!("Baud rate {x} is not supported by the Model 100"),
+        };
+
+        if !(6..=8).contains(&self.data_bits) {
+            bail!(
+                "Data bits {} not supported by the Model 100",
+                self.data_bits
+            );
+        }
+
+        if !(1..=2).contains(&self.stop_bits) {
+            bail!(
+                "Stop bits {} not supported by the Model 100",
+                self.stop_bits
+            );
+        }
+
+        let parity = match self.parity {
+            Parity::None => 'N',
+            Parity::Odd => 'O',
+            Parity::Even => 'E',
+        };
+
+        // The Model 100 has no RTS/CTS handshaking, only XON/XOFF
+        let xon = match self.flow_control {
+            FlowControl::Software => 'E',
+            FlowControl::None | FlowControl::Hardware => 'D',
+        };
+
+        Ok(format!(
+            "{baud}{}{parity}{}{xon}",
+            self.data_bits, self.stop_bits
+        ))
+    }
+}
+
+impl Default for SerialConfig {
+    fn default() -> Self {
+        Self {
+            port: "/dev/ttyUSB0".into(),
+            baud: 19_200,
+            flow_control: FlowControl::Software,
+            data_bits: 8,
+            parity: Parity::None,
+            stop_bits: 1,
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".into(),
+        }
+    }
+}
+
+impl From<FlowControl> for tokio_serial::FlowControl {
+    fn from(value: FlowControl) -> Self {
+        match value {
+            FlowControl::None => Self::None,
+            FlowControl::Software => Self::Software,
+            FlowControl::Hardware => Self::Hardware,
+        }
+    }
+}
+
+impl From<Parity> for tokio_serial::Parity {
+    fn from(value: Parity) -> Self {
+        match value {
+            Parity::None => Self::None,
+            Parity::Odd => Self::Odd,
+            Parity::Even => Self::Even,
+        }
+    }
+}
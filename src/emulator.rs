@@ -0,0 +1,180 @@
+use nalgebra::Vector2;
+
+use crate::screen::{Char, Screen, charset};
+
+/// Interprets the bytes sent to a Model 100 into the character grid it would
+/// display, so screens can be inspected without the real hardware.
+pub struct Emulator {
+    chars: [Char; Screen::SIZE],
+    cursor: Vector2<usize>,
+    inverted: bool,
+    scroll_locked: bool,
+    cursor_visible: bool,
+
+    escape: Escape,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Escape {
+    None,
+    Start,
+    Row,
+    Column(usize),
+}
+
+impl Emulator {
+    pub fn new() -> Self {
+        Self {
+            chars: [Char::default(); Screen::SIZE],
+            cursor: Vector2::zeros(),
+            inverted: false,
+            scroll_locked: false,
+            cursor_visible: true,
+
+            escape: Escape::None,
+        }
+    }
+
+    pub fn chars(&self) -> &[Char; Screen::SIZE] {
+        &self.chars
+    }
+
+    pub fn get(&self, pos: Vector2<usize>) -> Option<Char> {
+        Screen::index(pos).map(|x| self.chars[x])
+    }
+
+    pub fn cursor(&self) -> Vector2<usize> {
+        self.cursor
+    }
+
+    pub fn cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
+    /// Renders the screen as text, one line per row. Rows containing inverted
+    /// cells are followed by a line marking them with `^`.
+    pub fn snapshot(&self) -> String {
+        let mut out = String::new();
+        for row in self.chars.chunks(Screen::WIDTH) {
+            out.push('|');
+            out.extend(row.iter().map(|x| glyph(x.char())));
+            out.push_str("|\n");
+
+            if row.iter().any(|x| x.inverted()) {
+                out.push('|');
+                out.extend(row.iter().map(|x| if x.inverted() { '^' } else { ' ' }));
+                out.push_str("|\n");
+            }
+        }
+        out
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.feed_byte(byte);
+        }
+    }
+
+    fn feed_byte(&mut self, byte: u8) {
+        match self.escape {
+            Escape::None => self.control(byte),
+            Escape::Start => {
+                self.escape = Escape::None;
+                self.escape_sequence(byte);
+            }
+            Escape::Row => self.escape = Escape::Column(byte.saturating_sub(0x20) as usize),
+            Escape::Column(row) => {
+                self.escape = Escape::None;
+                let col = byte.saturating_sub(0x20) as usize;
+                self.cursor = Vector2::new(col.min(Screen::WIDTH - 1), row.min(Screen::HEIGHT - 1));
+            }
+        }
+    }
+
+    fn control(&mut self, byte: u8) {
+        match byte {
+            0x1B => self.escape = Escape::Start,
+            0x0C => {
+                self.chars.fill(Char::default());
+                self.cursor = Vector2::zeros();
+            }
+            0x0B => self.cursor = Vector2::zeros(),
+            b'\r' => self.cursor.x = 0,
+            b'\n' => self.line_feed(),
+            0x00..=0x1F => {}
+            _ => self.write(byte),
+        }
+    }
+
+    fn escape_sequence(&mut self, byte: u8) {
+        let (x, y) = (self.cursor.x, self.cursor.y);
+        match byte {
+            b'A' => self.cursor.y = y.saturating_sub(1),
+            b'B' => self.cursor.y = (y + 1).min(Screen::HEIGHT - 1),
+            b'C' => self.cursor.x = (x + 1).min(Screen::WIDTH - 1),
+            b'D' => self.cursor.x = x.saturating_sub(1),
+            b'p' => self.inverted = true,
+            b'q' => self.inverted = false,
+            b'V' => self.scroll_locked = true,
+            b'W' => self.scroll_locked = false,
+            b'P' => self.cursor_visible = true,
+            b'Q' => self.cursor_visible = false,
+            b'Y' => self.escape = Escape::Row,
+            b'K' => self.chars[Screen::index(self.cursor).unwrap()..][..Screen::WIDTH - x]
+                .fill(Char::default()),
+            b'J' => self.chars[Screen::index(self.cursor).unwrap()..].fill(Char::default()),
+            b'l' => self.chars[y * Screen::WIDTH..][..Screen::WIDTH].fill(Char::default()),
+            b'L' => {
+                self.chars[y * Screen::WIDTH..].rotate_right(Screen::WIDTH);
+                self.chars[y * Screen::WIDTH..][..Screen::WIDTH].fill(Char::default());
+            }
+            b'M' => {
+                self.chars[y * Screen::WIDTH..].rotate_left(Screen::WIDTH);
+                self.chars[Screen::SIZE - Screen::WIDTH..].fill(Char::default());
+            }
+            b'E' | b'j' => {
+                self.chars.fill(Char::default());
+                self.cursor = Vector2::zeros();
+            }
+            _ => {}
+        }
+    }
+
+    fn write(&mut self, byte: u8) {
+        let mut chr = Char::from(byte);
+        self.inverted.then(|| chr = chr.invert());
+
+        let index = Screen::index(self.cursor).unwrap();
+        self.chars[index] = chr;
+
+        if index + 1 < Screen::SIZE {
+            self.cursor = Screen::from_index(index + 1).unwrap();
+        } else if !self.scroll_locked {
+            self.cursor.x = 0;
+            self.line_feed();
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor.y + 1 < Screen::HEIGHT {
+            self.cursor.y += 1;
+        } else if !self.scroll_locked {
+            self.chars.copy_within(Screen::WIDTH.., 0);
+            self.chars[Screen::SIZE - Screen::WIDTH..].fill(Char::default());
+        }
+    }
+}
+
+/// Approximates a Model 100 character with a Unicode one for display.
+pub fn glyph(chr: u8) -> char {
+    match chr {
+        0x20..=0x7E | 0x80..=0xFF => charset::decode(chr),
+        _ => '·',
+    }
+}
+
+impl Default for Emulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
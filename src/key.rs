@@ -0,0 +1,196 @@
+//! Keys as the Model 100 sends them from TELCOM. Most keys are plain ASCII,
+//! the arrow keys and their SHIFT and CTRL combinations share control codes
+//! with CTRL+letters, and GRAPH and CODE type the upper half of the character
+//! set.
+//!
+//! F1 to F8, LABEL, PASTE and PRINT are handled by TELCOM itself and never
+//! reach the serial line, so there's nothing to decode them from.
+
+use std::{ops::BitOr, str::FromStr};
+
+use anyhow::{Context, Result, bail};
+
+/// A key pressed on the Model 100.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct KeyEvent {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum KeyCode {
+    /// A character, lowercase for CTRL+letter. GRAPH and CODE characters are
+    /// the byte they type, since the key pressed for them isn't sent.
+    Char(u8),
+    Enter,
+    Esc,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    /// BREAK, or CTRL+C, which the Model 100 treats the same.
+    Break,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub struct KeyModifiers(u8);
+
+/// Control codes with a meaning of their own. The rest are CTRL+letter.
+const SPECIAL: &[(u8, KeyCode, KeyModifiers)] = &[
+    (0x01, KeyCode::Left, KeyModifiers::SHIFT),
+    (0x02, KeyCode::Down, KeyModifiers::SHIFT),
+    (0x03, KeyCode::Break, KeyModifiers::NONE),
+    (0x06, KeyCode::Right, KeyModifiers::SHIFT),
+    (0x08, KeyCode::Backspace, KeyModifiers::NONE),
+    (0x09, KeyCode::Tab, KeyModifiers::NONE),
+    (0x0D, KeyCode::Enter, KeyModifiers::NONE),
+    (0x11, KeyCode::Left, KeyModifiers::CTRL),
+    (0x12, KeyCode::Right, KeyModifiers::CTRL),
+    (0x14, KeyCode::Up, KeyModifiers::SHIFT),
+    (0x17, KeyCode::Up, KeyModifiers::CTRL),
+    (0x1A, KeyCode::Down, KeyModifiers::CTRL),
+    (0x1B, KeyCode::Esc, KeyModifiers::NONE),
+    (0x1C, KeyCode::Right, KeyModifiers::NONE),
+    (0x1D, KeyCode::Left, KeyModifiers::NONE),
+    (0x1E, KeyCode::Up, KeyModifiers::NONE),
+    (0x1F, KeyCode::Down, KeyModifiers::NONE),
+    (0x7F, KeyCode::Delete, KeyModifiers::NONE),
+];
+
+impl KeyEvent {
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    pub const fn ctrl(letter: u8) -> Self {
+        Self::new(KeyCode::Char(letter), KeyModifiers::CTRL)
+    }
+
+    pub fn decode(byte: u8) -> Self {
+        if let Some(&(_, code, modifiers)) = SPECIAL.iter().find(|x| x.0 == byte) {
+            return Self::new(code, modifiers);
+        }
+
+        match byte {
+            0x00..=0x1F => Self::ctrl((byte + b'@').to_ascii_lowercase()),
+            // Accented letters, typed with CODE
+            0xA0..=0xDF => Self::new(KeyCode::Char(byte), KeyModifiers::CODE),
+            // Greek, symbols and graphics, typed with GRAPH
+            0x80..=0xFF => Self::new(KeyCode::Char(byte), KeyModifiers::GRAPH),
+            _ => Self::from(KeyCode::Char(byte)),
+        }
+    }
+
+    /// The byte the Model 100 sends for this key, if any.
+    pub fn encode(self) -> Option<u8> {
+        let special = SPECIAL
+            .iter()
+            .find(|x| x.1 == self.code && x.2 == self.modifiers);
+        if let Some(&(byte, ..)) = special {
+            return Some(byte);
+        }
+
+        let byte = match self.code {
+            KeyCode::Char(byte) => byte,
+            _ => return None,
+        };
+        let ctrl = (self.modifiers == KeyModifiers::CTRL)
+            .then(|| byte.to_ascii_uppercase().wrapping_sub(b'@'))
+            .filter(|&x| x < 0x20);
+        let encoded = ctrl.unwrap_or(byte);
+        (Self::decode(encoded) == self).then_some(encoded)
+    }
+
+    /// The character typed, if it's one that can be inserted into text.
+    pub fn char(self) -> Option<u8> {
+        match self.code {
+            KeyCode::Char(byte) if !self.modifiers.contains(KeyModifiers::CTRL) => Some(byte),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for KeyEvent {
+    type Err = anyhow::Error;
+
+    /// Parses names like `ctrl+r`, `shift+left` or `esc`, or a byte like
+    /// `0x93`. Keys sharing a code with another come out as the one it's
+    /// decoded to, so `ctrl+r` is CTRL+right.
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts: Vec<_> = s.split('+').collect();
+        let name = parts.pop().unwrap_or_default();
+
+        let mut modifiers = KeyModifiers::NONE;
+        for part in parts {
+            modifiers = modifiers
+                | match part.to_ascii_lowercase().as_str() {
+                    "shift" => KeyModifiers::SHIFT,
+                    "ctrl" => KeyModifiers::CTRL,
+                    "graph" => KeyModifiers::GRAPH,
+                    "code" => KeyModifiers::CODE,
+                    _ => bail!("Unknown modifier `{part}` in `{s}`"),
+                };
+        }
+
+        if let Some(hex) = name.strip_prefix("0x") {
+            let byte = u8::from_str_radix(hex, 16).with_context(|| format!("Invalid key `{s}`"))?;
+            return Ok(Self::decode(byte));
+        }
+
+        let code = match name.to_ascii_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "break" => KeyCode::Break,
+            "space" => KeyCode::Char(b' '),
+            _ if name.len() == 1 && name.is_ascii() => KeyCode::Char(name.as_bytes()[0]),
+            _ => bail!("Unknown key `{name}` in `{s}`"),
+        };
+
+        let byte = match (code, modifiers) {
+            (KeyCode::Char(chr), KeyModifiers::CTRL) => chr
+                .to_ascii_uppercase()
+                .checked_sub(b'@')
+                .filter(|&x| x < 0x20),
+            (KeyCode::Char(chr), KeyModifiers::SHIFT) => Some(chr.to_ascii_uppercase()),
+            _ => Self::new(code, modifiers).encode(),
+        };
+        let byte = byte.with_context(|| format!("The Model 100 doesn't send `{s}`"))?;
+        Ok(Self::decode(byte))
+    }
+}
+
+impl From<KeyCode> for KeyEvent {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+}
+
+impl KeyModifiers {
+    pub const NONE: Self = Self(0);
+    pub const SHIFT: Self = Self(1);
+    pub const CTRL: Self = Self(2);
+    pub const GRAPH: Self = Self(4);
+    pub const CODE: Self = Self(8);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for KeyModifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
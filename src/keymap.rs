@@ -0,0 +1,120 @@
+//! Global shortcuts, checked before keys reach the module on screen.
+
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::key::KeyEvent;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    /// Sends the whole screen again.
+    Redraw,
+    /// Sends the module on screen to the background.
+    Menu,
+    /// Cycles through the running modules.
+    Switch,
+    /// Saves the screen as a text file.
+    Screenshot,
+    /// Lists the shortcuts.
+    Help,
+    /// Reads the config file again.
+    Reload,
+}
+
+/// Which key triggers each action, from the `[keymap]` table. A binding can be
+/// turned off with `"none"`, and modules can take keys for themselves with
+/// `passthrough`.
+#[derive(Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keymap {
+    pub redraw: Binding,
+    pub menu: Binding,
+    pub switch: Binding,
+    pub screenshot: Binding,
+    pub help: Binding,
+    pub reload: Binding,
+    /// Where screenshots are saved.
+    pub screenshot_dir: PathBuf,
+}
+
+/// A key as written in the config, like `ctrl+r`.
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Binding {
+    key: Option<KeyEvent>,
+    name: String,
+}
+
+impl Action {
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Redraw => "Redraw screen",
+            Action::Menu => "Back to menu",
+            Action::Switch => "Next module",
+            Action::Screenshot => "Save screenshot",
+            Action::Help => "Show this help",
+            Action::Reload => "Reload config",
+        }
+    }
+}
+
+impl Keymap {
+    /// The action bound to a key, if any.
+    pub fn action(&self, key: KeyEvent) -> Option<Action> {
+        self.bindings().find(|x| x.1.key == Some(key)).map(|x| x.0)
+    }
+
+    pub fn bindings(&self) -> impl Iterator<Item = (Action, &Binding)> {
+        [
+            (Action::Redraw, &self.redraw),
+            (Action::Menu, &self.menu),
+            (Action::Switch, &self.switch),
+            (Action::Screenshot, &self.screenshot),
+            (Action::Help, &self.help),
+            (Action::Reload, &self.reload),
+        ]
+        .into_iter()
+    }
+}
+
+impl Binding {
+    /// None when turned off.
+    pub fn key(&self) -> Option<KeyEvent> {
+        self.key
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = anyhow::Error;
+
+    fn try_from(name: String) -> Result<Self> {
+        let key = match name.eq_ignore_ascii_case("none") {
+            true => None,
+            false => Some(name.parse()?),
+        };
+        Ok(Self { key, name })
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let binding = |name: &str| Binding::try_from(name.to_owned()).unwrap();
+        Self {
+            // CTRL+R is CTRL+right, which the line editor uses
+            redraw: binding("ctrl+d"),
+            menu: binding("ctrl+p"),
+            switch: binding("ctrl+n"),
+            screenshot: binding("ctrl+o"),
+            help: binding("ctrl+k"),
+            reload: binding("ctrl+l"),
+            screenshot_dir: PathBuf::from("."),
+        }
+    }
+}
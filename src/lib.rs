@@ -0,0 +1,157 @@
+use std::{future, pin::pin, time::Duration};
+
+use anyhow::Result;
+use nalgebra::Vector2;
+use sd_notify::NotifyState;
+use tokio::{select, time};
+use tracing::{debug, info, warn};
+
+pub mod config;
+pub mod emulator;
+pub mod key;
+pub mod keymap;
+pub mod modules;
+pub mod screen;
+pub mod state;
+pub mod timer;
+pub mod transport;
+pub mod widgets;
+
+use crate::{
+    config::SerialConfig,
+    modules::{Module, menu::Menu},
+    screen::Screen,
+    state::State,
+};
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Runs the menu against a connected Model 100 until it's exited or the
+/// transport fails.
+pub async fn run(screen: &mut State, menu: &mut Menu) -> Result<()> {
+    menu.init(screen).await?;
+    run_menu(screen, menu).await
+}
+
+/// Like [`run`], but opens the transport itself and keeps reopening it when
+/// the adapter is unplugged or the Model 100 is switched off. The menu and the
+/// open module carry on where they were once it's back.
+///
+/// Stops when `shutdown` completes, letting running modules clean up and
+/// leaving "Bridge offline" on the screen. Reports its status to systemd when
+/// started as a notify service.
+pub async fn serve(
+    serial: &SerialConfig,
+    menu: &mut Menu,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()> {
+    let mut shutdown = pin!(shutdown);
+    notify(&[NotifyState::Ready]);
+
+    let transport = select! {
+        transport = connect(serial) => transport,
+        _ = &mut shutdown => return Ok(()),
+    };
+    let mut screen = State::new(transport).await?;
+    menu.init(&mut screen).await?;
+
+    let result = select! {
+        result = reconnecting(&mut screen, menu, serial) => result,
+        _ = shutdown => {
+            info!("Shutting down");
+            menu.shutdown(&mut screen).await;
+            go_offline(&mut screen).await;
+            Ok(())
+        }
+    };
+
+    notify(&[NotifyState::Stopping]);
+    result
+}
+
+async fn reconnecting(screen: &mut State, menu: &mut Menu, serial: &SerialConfig) -> Result<()> {
+    loop {
+        match run_menu(screen, menu).await {
+            Err(err) if screen.disconnected() => warn!("Disconnected: {err:#}"),
+            result => return result,
+        }
+
+        while let Err(err) = screen.reconnect(connect(serial).await).await {
+            warn!("Disconnected: {err:#}");
+        }
+    }
+}
+
+/// Opens the transport, retrying with exponential backoff until it works.
+async fn connect(serial: &SerialConfig) -> Box<dyn transport::Transport> {
+    notify(&[NotifyState::Status(&format!(
+        "Connecting to {}",
+        serial.port
+    ))]);
+
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        match transport::open(serial).await {
+            Ok(transport) => {
+                info!("Connected to {}", serial.port);
+                notify(&[NotifyState::Status(&format!(
+                    "Connected to {}",
+                    serial.port
+                ))]);
+                return transport;
+            }
+            Err(err) => warn!("{err:#}, retrying in {}s", backoff.as_secs()),
+        }
+
+        time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// Leaves a message on the Model 100 so it's clear nothing is answering.
+async fn go_offline(screen: &mut State) {
+    if screen.disconnected() {
+        return;
+    }
+
+    let message = b"Bridge offline";
+    let pos = Vector2::new((Screen::WIDTH - message.len()) / 2, Screen::HEIGHT / 2 - 1);
+    screen.clear();
+    screen.write_string(pos, message);
+    if let Err(err) = screen.redraw().await {
+        warn!("Failed to show shutdown message: {err:#}");
+    }
+}
+
+/// Tells systemd about the bridge's status, which does nothing when it wasn't
+/// started as a notify service.
+fn notify(state: &[NotifyState]) {
+    if let Err(err) = sd_notify::notify(state) {
+        warn!("Failed to notify systemd: {err}");
+    }
+}
+
+async fn run_menu(screen: &mut State, menu: &mut Menu) -> Result<()> {
+    loop {
+        let timeout = screen.next_timeout();
+        select! {
+            key = screen.read_key() => menu.on_key(screen, key?).await?,
+            _ = async {
+                if let Some(time) = timeout {
+                    tokio::time::sleep_until(time).await;
+                } else {
+                    future::pending::<()>().await;
+                }
+            } => {
+                let event = screen.pop_timeout().unwrap();
+                debug!("{:?} for {}", event.timer, screen.owner());
+                menu.callback(screen, event).await?;
+            }
+        }
+
+        if screen.take_exit() {
+            return Ok(());
+        }
+    }
+}
@@ -1,45 +1,54 @@
-use std::future;
+use std::{env, io};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use clap::Parser;
 use tokio::{
-    io::{self, AsyncReadExt, BufWriter},
     select,
+    signal::unix::{SignalKind, signal},
 };
-use tokio_serial::{FlowControl, SerialPortBuilderExt};
+use tracing_subscriber::EnvFilter;
 
-mod modules;
-mod screen;
-mod state;
-
-use crate::{
-    modules::{Module, menu::Menu},
-    state::State,
+use model_100_serial::{
+    config::{Args, Config},
+    modules::{menu::Menu, registry::Registry},
 };
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> Result<()> {
-    let port = tokio_serial::new("/dev/ttyUSB0", 19_200)
-        .flow_control(FlowControl::Software)
-        .open_native_async()?;
-    let (mut rx, tx) = io::split(port);
+    let args = Args::parse();
+    let config = Config::load(&args)?;
+
+    if args.stat {
+        println!("{}", config.serial.stat()?);
+        return Ok(());
+    }
 
-    let mut screen = State::new(BufWriter::new(tx)).await?;
-    let mut menu = Menu::default();
-    menu.init(&mut screen).await?;
+    // The journal adds its own timestamps and doesn't understand colors
+    let journal = env::var_os("JOURNAL_STREAM").is_some();
+    let filter = EnvFilter::try_new(&config.log.level)
+        .with_context(|| format!("Invalid log level `{}`", config.log.level))?;
+    let logger = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr)
+        .with_ansi(!journal);
+    match journal {
+        true => logger.without_time().init(),
+        false => logger.init(),
+    }
 
-    loop {
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let shutdown = async {
         select! {
-            key = rx.read_u8() => menu.on_key(&mut screen, key?).await?,
-            _ = async {
-                if let Some(t) = screen.timeouts.peek() {
-                    tokio::time::sleep_until(t.time).await;
-                } else {
-                    future::pending::<()>().await;
-                }
-            } => {
-                let kind = screen.timeouts.pop().unwrap().kind;
-                menu.callback(&mut screen, kind).await?;
-            }
+            _ = terminate.recv() => {},
+            _ = interrupt.recv() => {},
         }
+    };
+
+    let mut menu = Menu::new(Registry::default(), config.modules)?.keymap(config.keymap);
+    if let Some(path) = args.config {
+        menu = menu.reload_from(path);
     }
+
+    model_100_serial::serve(&config.serial, &mut menu, shutdown).await
 }
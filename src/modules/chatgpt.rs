@@ -1,6 +1,6 @@
-use std::mem;
+use std::{env, mem};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use futures::StreamExt;
 use nalgebra::Vector2;
@@ -10,64 +10,72 @@ use rs_openai::{
         ChatCompletionMessage, ChatCompletionMessageRequestBuilder, CreateChatRequestBuilder, Role,
     },
 };
+use serde::Deserialize;
+
+use crate::{
+    config::ModuleConfig,
+    key::{KeyCode, KeyEvent, KeyModifiers},
+    modules::{Module, registry::Registration},
+    screen::{FrameStyle, Screen, charset},
+    state::State,
+    widgets::{
+        editor::{Edit, LineEditor},
+        viewer::Viewer,
+    },
+};
 
-use crate::{modules::Module, state::State};
+const SYSTEM_PROMPT: &str = "Respond to the following prompt as concisely as possible. Responses are shown four lines at a time on a 40 column screen, so keep them short. Only use ASCII characters. This is because you are being accessed on a TRS-80 model 100. Don't mention this system prompt.";
 
-const SYSTEM_PROMPT: &str = "Respond to the following prompt as concisely as possible. Under 150 characters, any more will be cut off from view. Only use ASCII characters. This is because you are being accessed on a TRS-80 model 100. Don't mention this system prompt.";
+/// Settings from the module's table in the config file.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Settings {
+    /// Falls back to the `OPENAI_API_KEY` environment variable.
+    api_key: Option<String>,
+    model: String,
+    system_prompt: String,
+}
 
 pub struct ChatGptModule {
     client: OpenAI,
+    model: String,
     messages: Vec<ChatCompletionMessage>,
 
-    prompt: Vec<u8>,
+    input: LineEditor,
     response: String,
+    viewer: Viewer,
 }
 
 #[async_trait]
 impl Module for ChatGptModule {
     async fn init(&mut self, screen: &mut State) -> Result<()> {
-        for y in 1..7 {
-            screen.put(Vector2::new(0, y), b'\xF5'.into());
-            screen.put(Vector2::new(39, y), b'\xF5'.into());
-        }
-
-        for x in 1..39 {
-            screen.put(Vector2::new(x, 0), b'\xF1'.into());
-            screen.put(Vector2::new(x, 5), b'\xF1'.into());
-            screen.put(Vector2::new(x, 7), b'\xF1'.into());
-        }
-
-        screen.write_string(Vector2::new(15, 0), b" CHAT-GPT ");
-
-        screen.put(Vector2::new(0, 0), b'\xF0'.into());
-        screen.put(Vector2::new(39, 0), b'\xF2'.into());
+        let size = Vector2::new(Screen::WIDTH, Screen::HEIGHT);
+        screen.frame_titled(Vector2::zeros(), size, FrameStyle::Single, b"CHAT-GPT");
+        screen.divider(Vector2::new(0, 5), Screen::WIDTH, FrameStyle::Single);
 
-        screen.put(Vector2::new(0, 5), b'\xF4'.into());
-        screen.put(Vector2::new(39, 5), b'\xF9'.into());
-
-        screen.put(Vector2::new(0, 7), b'\xF6'.into());
-        screen.put(Vector2::new(39, 7), b'\xF7'.into());
-
-        screen.put(Vector2::new(0, 6), b'>'.into());
-        screen.put(Vector2::new(1, 6), b'\xE9'.into());
+        self.draw_input(screen);
 
         screen.draw().await?;
         Ok(())
     }
 
-    async fn on_key(&mut self, screen: &mut State, key: u8) -> Result<()> {
-        if key == 0x1B {
+    async fn on_key(&mut self, screen: &mut State, key: KeyEvent) -> Result<()> {
+        if key.code == KeyCode::Esc {
             screen.exit();
             return Ok(());
         }
 
-        if key == 0x08 {
-            if self.prompt.len() > 1 {
-                self.prompt.pop();
-            }
-        } else if key == 0x0D {
-            let prompt = mem::take(&mut self.prompt);
-            self.prompt = vec![b'>'];
+        // Up and down recall earlier prompts, the other scrolling keys move
+        // through the response
+        let vertical = matches!(key.code, KeyCode::Up | KeyCode::Down);
+        if !(vertical && key.modifiers == KeyModifiers::NONE) && self.viewer.on_key(key) {
+            self.draw_response(screen);
+            screen.draw().await?;
+            return Ok(());
+        }
+
+        if let Edit::Submit(prompt) = self.input.on_key(key) {
+            self.draw_input(screen);
 
             if !self.response.is_empty() {
                 let msg = ChatCompletionMessageRequestBuilder::default()
@@ -76,65 +84,99 @@ impl Module for ChatGptModule {
                     .build()?;
                 self.messages.push(msg);
             }
+            self.viewer.clear();
 
             let msg = ChatCompletionMessageRequestBuilder::default()
                 .role(Role::User)
-                .content(String::from_utf8_lossy(&prompt[1..]))
+                .content(charset::decode_bytes(&prompt))
                 .build()?;
             self.messages.push(msg);
 
             let req = CreateChatRequestBuilder::default()
-                .model("gpt-4")
+                .model(&self.model)
                 .messages(self.messages.clone())
                 .stream(true)
                 .build()?;
 
             let mut stream = self.client.chat().create_with_stream(&req).await?;
             while let Some(response) = stream.next().await {
-                let choice = &response.unwrap().choices[0];
-                if let Some(ref content) = choice.delta.content {
+                let response = response?;
+                let content = response
+                    .choices
+                    .first()
+                    .and_then(|x| x.delta.content.as_ref());
+                if let Some(content) = content {
                     self.response.push_str(content);
+                    self.viewer.push(&charset::encode_text(content));
                 }
 
-                screen.write_string_wrapped(Vector2::new(1, 1), self.response.as_bytes(), 38);
+                self.draw_response(screen);
                 screen.draw().await?;
             }
-        } else {
-            self.prompt.push(key);
         }
 
-        let display = &self.prompt[self.prompt.len().saturating_sub(39)..];
-        screen.rect(Vector2::new(0, 6), Vector2::new(39, 1), b' '.into());
-        screen.rect(Vector2::new(1, 1), Vector2::new(38, 4), b' '.into());
-
-        screen.write_string(Vector2::new(0, 6), display);
-        screen.put(Vector2::new(39, 6), b'\xF5'.into());
-        screen.put(Vector2::new(display.len(), 6), b'\xE9'.into());
-
-        screen.write_string_wrapped(Vector2::new(1, 1), self.response.as_bytes(), 38);
-
+        self.draw_input(screen);
+        self.draw_response(screen);
         screen.draw().await?;
         Ok(())
     }
 }
 
-impl Default for ChatGptModule {
-    fn default() -> Self {
-        Self {
+impl ChatGptModule {
+    fn draw_input(&mut self, screen: &mut State) {
+        let mut window = screen.window(Vector2::new(0, 6), Vector2::new(39, 1));
+        self.input.draw(&mut window);
+    }
+
+    fn draw_response(&mut self, screen: &mut State) {
+        let mut window = screen.window(Vector2::new(1, 1), Vector2::new(38, 4));
+        self.viewer.draw(&mut window);
+    }
+}
+
+impl ChatGptModule {
+    pub const REGISTRATION: Registration = Registration {
+        kind: "chatgpt",
+        name: "Chat-GPT",
+        description: "Ask OpenAI's models a question",
+        hotkey: None,
+        new: |config| Ok(Box::new(Self::new(config)?)),
+    };
+
+    pub fn new(config: &ModuleConfig) -> Result<Self> {
+        let settings: Settings = config.settings()?;
+        let api_key = match settings.api_key {
+            Some(key) => key,
+            None => env::var("OPENAI_API_KEY")
+                .context("No API key, set `api_key` or `OPENAI_API_KEY`")?,
+        };
+
+        Ok(Self {
             client: OpenAI {
-                api_key: env!("OPENAPI_KEY").to_owned(),
+                api_key,
                 org_id: None,
             },
+            model: settings.model,
             messages: vec![
                 ChatCompletionMessageRequestBuilder::default()
                     .role(Role::System)
-                    .content(SYSTEM_PROMPT)
-                    .build()
-                    .unwrap(),
+                    .content(settings.system_prompt)
+                    .build()?,
             ],
 
-            prompt: vec![b'>'],
+            input: LineEditor::new(b">"),
             response: String::new(),
+            viewer: Viewer::new(),
+        })
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            api_key: None,
+            model: "gpt-4".into(),
+            system_prompt: SYSTEM_PROMPT.into(),
         }
     }
 }
@@ -3,14 +3,24 @@ use async_trait::async_trait;
 use enigo::{Direction, Enigo, Key, Keyboard, Settings};
 use nalgebra::Vector2;
 
-use crate::{modules::Module, state::State};
+use crate::{
+    config::ModuleConfig,
+    key::{KeyCode, KeyEvent, KeyModifiers},
+    modules::{Module, registry::Registration},
+    screen::charset,
+    state::State,
+};
 
-pub struct KeyboardModule {
-    enigo: Enigo,
+/// GRAPH+Q.
+const EXIT_KEY: KeyEvent = KeyEvent::new(KeyCode::Char(0x93), KeyModifiers::GRAPH);
+
+/// Types on the host with `K`, which is only swapped out in tests.
+pub struct KeyboardModule<K = Enigo> {
+    keyboard: K,
 }
 
 #[async_trait]
-impl Module for KeyboardModule {
+impl<K: Keyboard + Send> Module for KeyboardModule<K> {
     async fn init(&mut self, screen: &mut State) -> Result<()> {
         screen.write_string(Vector2::new(0, 0), b"Keyboard mode.");
         screen.write_string(Vector2::new(0, 1), b"Press GRAPH+Q to exit.");
@@ -18,41 +28,78 @@ impl Module for KeyboardModule {
         Ok(())
     }
 
-    async fn on_key(&mut self, screen: &mut State, key: u8) -> Result<()> {
-        dbg!(key);
-
-        if key == 0x93 {
+    async fn on_key(&mut self, screen: &mut State, key: KeyEvent) -> Result<()> {
+        if key == EXIT_KEY {
             screen.exit();
             return Ok(());
         }
 
-        if matches!(key, 1..=7 | 9..=12 | 14..=26) {
-            let _ = self.enigo.key(Key::Control, Direction::Press);
-            let _ = self
-                .enigo
-                .key(Key::Unicode((key - 1 + b'a') as char), Direction::Click);
-            let _ = self.enigo.key(Key::Control, Direction::Release);
-        } else {
-            let key = match key {
-                0x1B => Key::Escape,
-                0x1D => Key::LeftArrow,
-                0x1C => Key::RightArrow,
-                0x1E => Key::UpArrow,
-                0x1F => Key::DownArrow,
-                _ => Key::Unicode(key as char),
-            };
-
-            let _ = self.enigo.key(key, Direction::Click);
+        // SHIFT and CTRL+arrows send the same code as a CTRL+letter, which is
+        // what programs on the host are more likely to want
+        let key = match key.encode() {
+            Some(byte @ 0x01..=0x1A) if key.modifiers != KeyModifiers::NONE => {
+                KeyEvent::ctrl(byte + b'`')
+            }
+            _ => key,
+        };
+
+        let code = match key.code {
+            KeyCode::Char(chr) => match charset::decode(chr) {
+                // Not known to be anything on the host
+                char::REPLACEMENT_CHARACTER => return Ok(()),
+                chr => Key::Unicode(chr),
+            },
+            KeyCode::Break => Key::Unicode('c'),
+            KeyCode::Enter => Key::Return,
+            KeyCode::Esc => Key::Escape,
+            KeyCode::Tab => Key::Tab,
+            KeyCode::Backspace => Key::Backspace,
+            KeyCode::Delete => Key::Delete,
+            KeyCode::Left => Key::LeftArrow,
+            KeyCode::Right => Key::RightArrow,
+            KeyCode::Up => Key::UpArrow,
+            KeyCode::Down => Key::DownArrow,
+        };
+
+        // GRAPH and CODE are left out, the character they typed is sent
+        let ctrl = key.modifiers.contains(KeyModifiers::CTRL) || key.code == KeyCode::Break;
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let held = [(ctrl, Key::Control), (shift, Key::Shift)];
+
+        for (_, modifier) in held.iter().filter(|x| x.0) {
+            let _ = self.keyboard.key(*modifier, Direction::Press);
+        }
+        let _ = self.keyboard.key(code, Direction::Click);
+        for (_, modifier) in held.iter().filter(|x| x.0).rev() {
+            let _ = self.keyboard.key(*modifier, Direction::Release);
         }
 
         Ok(())
     }
+
+    /// Every key goes to the host, GRAPH+Q is the only way out.
+    fn raw_input(&self) -> bool {
+        true
+    }
 }
 
-impl Default for KeyboardModule {
-    fn default() -> Self {
-        Self {
-            enigo: Enigo::new(&Settings::default()).unwrap(),
-        }
+impl KeyboardModule<Enigo> {
+    pub const REGISTRATION: Registration = Registration {
+        kind: "keyboard",
+        name: "Keyboard",
+        description: "Type on the host computer",
+        hotkey: None,
+        new: |config| Ok(Box::new(Self::new(config)?)),
+    };
+
+    /// Takes no settings.
+    pub fn new(_config: &ModuleConfig) -> Result<Self> {
+        Ok(Self::with_keyboard(Enigo::new(&Settings::default())?))
+    }
+}
+
+impl<K> KeyboardModule<K> {
+    pub fn with_keyboard(keyboard: K) -> Self {
+        Self { keyboard }
     }
 }
@@ -1,97 +1,674 @@
-use anyhow::Result;
+use std::{
+    any::Any,
+    fs,
+    future::Future,
+    mem,
+    panic::AssertUnwindSafe,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Result, anyhow, bail};
 use async_trait::async_trait;
+use futures::FutureExt;
 use nalgebra::Vector2;
+use tokio::time;
+use tracing::{error, info, warn};
 
 use crate::{
-    modules::{Module, chatgpt::ChatGptModule, keyboard::KeyboardModule, printer::PrinterModule},
+    config::{Config, ModuleConfig},
+    key::KeyEvent,
+    keymap::{Action, Keymap},
+    modules::{
+        Module,
+        registry::{Registration, Registry},
+    },
+    screen::{Char, Screen, charset},
     state::State,
+    timer::TimerEvent,
+    widgets::{
+        dialog,
+        list::{Item, List, Select},
+    },
 };
 
-#[derive(Default)]
+/// How long a module's exit hook gets when it can't refuse, like when the
+/// bridge is shutting down, in case it waits for a key that isn't coming.
+const EXIT_TIMEOUT: Duration = Duration::from_secs(2);
+
 pub struct Menu {
-    selection: u8,
-    module: Option<Box<dyn Module + Send>>,
+    registry: Registry,
+    entries: Vec<Entry>,
+    list: List,
+    /// Modules that were opened and haven't exited, at most one per entry.
+    running: Vec<Running>,
+    /// Id of the running module on screen, or none when the menu is.
+    active: Option<u32>,
+    next_id: u32,
+    keymap: Keymap,
+    /// Where to read the entries from again for [`Action::Reload`].
+    config: Option<PathBuf>,
+}
+
+struct Entry {
+    registration: Registration,
+    config: ModuleConfig,
+}
+
+struct Running {
+    /// Tells its timers apart from other modules', never zero.
+    id: u32,
+    entry: usize,
+    module: Box<dyn Module + Send>,
+    /// Its screen while in the background.
+    chars: Box<[Char; Screen::SIZE]>,
 }
 
 impl Menu {
-    async fn draw(&mut self, screen: &mut State) -> Result<()> {
-        const OPTIONS: &[&[u8]] = &[b"Chat-GPT", b"Printer", b"Keyboard", b"Exit"];
+    /// Builds the menu from config entries, or every registered module when
+    /// there are none.
+    pub fn new(registry: Registry, entries: Vec<ModuleConfig>) -> Result<Self> {
+        let mut menu = Self {
+            registry,
+            entries: Vec::new(),
+            list: List::new(Vec::new()),
+            running: Vec::new(),
+            active: None,
+            next_id: 1,
+            keymap: Keymap::default(),
+            config: None,
+        };
 
-        screen.clear();
-        for (i, option) in OPTIONS.iter().enumerate() {
-            if self.selection == i as u8 {
-                screen.put(Vector2::new(18 - option.len() / 2, i + 1), b'>'.into());
-                screen.put(
-                    Vector2::new(21 - option.len() / 2 + option.len(), i + 1),
-                    b'<'.into(),
+        let entries = menu.entries(entries)?;
+        menu.set_entries(entries);
+        Ok(menu)
+    }
+
+    /// Allows reloading the entries and keymap from a config file with
+    /// [`Action::Reload`]. Modules pick up their new settings the next time
+    /// they're opened, and ones whose entry was removed are closed.
+    pub fn reload_from(mut self, path: PathBuf) -> Self {
+        self.config = Some(path);
+        self
+    }
+
+    pub fn keymap(mut self, keymap: Keymap) -> Self {
+        self.keymap = keymap;
+        self
+    }
+
+    /// Entries for the configured modules, or every registered one if none
+    /// are configured.
+    fn entries(&self, configs: Vec<ModuleConfig>) -> Result<Vec<Entry>> {
+        let configs = match configs.is_empty() {
+            true => self
+                .registry
+                .iter()
+                .map(|x| ModuleConfig::new(x.kind))
+                .collect(),
+            false => configs,
+        };
+
+        let mut entries = Vec::new();
+        for config in configs {
+            let registration = *self.registry.get(&config.kind)?;
+            if !config.enabled {
+                continue;
+            }
+
+            let entry = Entry {
+                registration,
+                config,
+            };
+            // Running modules are matched to their entry by kind and name
+            if entries.iter().any(|x| entry.same(x)) {
+                bail!(
+                    "Module `{}` is listed twice, give one of them another `name`",
+                    entry.name()
                 );
             }
+            entries.push(entry);
+        }
+
+        Ok(entries)
+    }
+
+    /// Replaces the entries, moving running modules to the ones with the same
+    /// kind and name. Modules without one must have been closed already.
+    fn set_entries(&mut self, entries: Vec<Entry>) {
+        let old = mem::replace(&mut self.entries, entries);
+        for running in &mut self.running {
+            let old = &old[running.entry];
+            running.entry = (self.entries.iter().position(|x| x.same(old)))
+                .expect("running module's entry was removed");
+        }
+
+        self.update_list();
+    }
+
+    /// Rebuilds the list after entries were changed or modules opened or
+    /// closed.
+    fn update_list(&mut self) {
+        let mut items = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let mut name = charset::encode_str(entry.name());
+            if self.running.iter().any(|x| x.entry == i) {
+                name.extend_from_slice(b" *");
+            }
+            items.push(Item::new(&name).hotkey(entry.registration.hotkey));
+        }
+        items.push(Item::new(b"Exit"));
+
+        let selected = self.list.selected();
+        self.list = List::new(items);
+        if let Some(selected) = selected {
+            self.list.select(selected.min(self.entries.len()));
+        }
+    }
+
+    /// A running module whose entry isn't among `entries`.
+    fn removed(&self, entries: &[Entry]) -> Option<usize> {
+        self.running.iter().position(|running| {
+            let entry = &self.entries[running.entry];
+            !entries.iter().any(|x| x.same(entry))
+        })
+    }
+
+    async fn reload(&mut self, screen: &mut State) -> Result<()> {
+        let Some(path) = self.config.clone() else {
+            return Ok(());
+        };
+
+        let result = Config::read(&path)
+            .and_then(|config| Ok((self.entries(config.modules)?, config.keymap)));
+        match result {
+            Ok((entries, keymap)) => {
+                while let Some(index) = self.removed(&entries) {
+                    info!("Closing {}, it was removed", self.name(index));
+                    self.force_exit(screen, index).await;
+                    self.close(screen, index);
+                }
+
+                self.set_entries(entries);
+                info!("Reloaded {}", path.display());
+                self.keymap = keymap.clone();
+                screen.set_keymap(keymap);
+            }
+            Err(err) => {
+                error!("{err:#}");
+                let message = format!("Error: {err:#}");
+                dialog::alert(screen, &charset::encode_text(&message)).await?;
+            }
+        }
 
-            screen.write_string_inverted(
-                Vector2::new(20 - option.len() / 2, i + 1),
-                option,
-                self.selection == i as u8,
-            );
+        // A module on screen stays there, unless the reload closed it
+        match self.active {
+            Some(_) => Ok(()),
+            None => self.draw(screen).await,
+        }
+    }
+
+    async fn draw(&mut self, screen: &mut State) -> Result<()> {
+        screen.clear();
+        let mut window = screen.window(
+            Vector2::new(0, 1),
+            Vector2::new(Screen::WIDTH, Screen::HEIGHT - 2),
+        );
+        self.list.draw(&mut window);
+
+        let entry = self.list.selected().and_then(|x| self.entries.get(x));
+        if let Some(entry) = entry {
+            let description = charset::encode_str(entry.registration.description);
+            let x = Screen::WIDTH.saturating_sub(description.len()) / 2;
+            screen.write_string(Vector2::new(x, Screen::HEIGHT - 1), &description);
         }
 
         screen.draw().await?;
         Ok(())
     }
+
+    fn find(&self, id: u32) -> Option<usize> {
+        self.running.iter().position(|x| x.id == id)
+    }
+
+    fn name(&self, index: usize) -> &str {
+        self.entries[self.running[index].entry].name()
+    }
+
+    /// Switches to the entry's module, opening it if it isn't running yet.
+    async fn open(&mut self, screen: &mut State, entry: usize) -> Result<()> {
+        let index = self.running.iter().position(|x| x.entry == entry);
+        if let Some(index) = index {
+            return self.resume(screen, index).await;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        screen.set_owner(id);
+
+        let entry_ref = &self.entries[entry];
+        info!("Opening {}", entry_ref.name());
+        screen.clear();
+        let result = catch(async {
+            let mut module = (entry_ref.registration.new)(&entry_ref.config)?;
+            module.init(screen).await?;
+            Ok(module)
+        })
+        .await;
+
+        match result {
+            Ok(module) => {
+                self.running.push(Running {
+                    id,
+                    entry,
+                    module,
+                    chars: Box::new([Char::default(); Screen::SIZE]),
+                });
+                self.active = Some(id);
+                self.update_list();
+                self.after_call(screen, Ok(())).await
+            }
+            Err(err) if screen.disconnected() => Err(err),
+            Err(err) => {
+                let name = self.entries[entry].name().to_owned();
+                error!("{name} failed: {err:#}");
+                screen.cancel_all();
+                show_error(screen, &name, &err).await?;
+                self.draw(screen).await
+            }
+        }
+    }
+
+    /// Brings a running module to the screen.
+    async fn resume(&mut self, screen: &mut State, index: usize) -> Result<()> {
+        info!("Resuming {}", self.name(index));
+        let running = &mut self.running[index];
+        self.active = Some(running.id);
+        screen.set_owner(running.id);
+        screen.restore(&running.chars);
+        screen.draw().await?;
+
+        let result = catch(running.module.on_resume(screen)).await;
+        self.after_call(screen, result).await
+    }
+
+    /// Sends the module on screen to the background, keeping its screen and
+    /// timers. Returns false if it failed and was closed instead.
+    async fn suspend(&mut self, screen: &mut State) -> Result<bool> {
+        let Some(index) = self.active.and_then(|x| self.find(x)) else {
+            return Ok(true);
+        };
+
+        let running = &mut self.running[index];
+        screen.set_owner(running.id);
+        let result = catch(running.module.on_suspend(screen)).await;
+        if let Err(err) = result {
+            return match screen.disconnected() {
+                true => Err(err),
+                false => self.fail(screen, index, err).await.map(|()| false),
+            };
+        }
+
+        let running = &mut self.running[index];
+        *running.chars = *screen.chars();
+        self.active = None;
+        screen.take_exit();
+        info!("Suspended {}", self.name(index));
+        Ok(true)
+    }
+
+    async fn run_action(&mut self, screen: &mut State, action: Action) -> Result<()> {
+        match action {
+            Action::Redraw => screen.redraw().await,
+            Action::Menu => {
+                if self.active.is_some() && self.suspend(screen).await? {
+                    self.draw(screen).await?;
+                }
+                Ok(())
+            }
+            Action::Switch => self.switch(screen).await,
+            Action::Screenshot => self.screenshot(screen).await,
+            Action::Help => self.help(screen).await,
+            Action::Reload => self.reload(screen).await,
+        }
+    }
+
+    /// Global shortcuts the module on screen takes for itself.
+    fn passthrough(&self) -> &[Action] {
+        match self.active.and_then(|x| self.find(x)) {
+            Some(index) => &self.entries[self.running[index].entry].config.passthrough,
+            None => &[],
+        }
+    }
+
+    /// Saves the screen as text, named after the time it was taken.
+    async fn screenshot(&mut self, screen: &mut State) -> Result<()> {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = self
+            .keymap
+            .screenshot_dir
+            .join(format!("screenshot-{time}.txt"));
+
+        let message = match fs::write(&path, screen.text()) {
+            Ok(()) => {
+                info!("Saved {}", path.display());
+                format!("Saved {}", path.display())
+            }
+            Err(err) => {
+                error!("Failed to save {}: {err}", path.display());
+                format!("Failed to save screenshot: {err}")
+            }
+        };
+        dialog::alert(screen, &charset::encode_text(&message)).await
+    }
+
+    async fn help(&mut self, screen: &mut State) -> Result<()> {
+        let passthrough = self.passthrough();
+        let mut help = String::new();
+        for (action, binding) in self.keymap.bindings() {
+            // Help was just pressed, leaving it out makes room for reload
+            let shown = match action {
+                Action::Help => false,
+                Action::Reload => self.config.is_some(),
+                _ => true,
+            };
+            if shown && binding.key().is_some() && !passthrough.contains(&action) {
+                let name = binding.name().to_uppercase();
+                help.push_str(&format!("{name} {}\n", action.description()));
+            }
+        }
+
+        dialog::alert(screen, &charset::encode_text(help.trim_end())).await
+    }
+
+    /// Cycles to the running module after the one on screen.
+    async fn switch(&mut self, screen: &mut State) -> Result<()> {
+        let next = match self.active.and_then(|x| self.find(x)) {
+            Some(index) => index + 1,
+            None => 0,
+        };
+        let Some(id) = self
+            .running
+            .get(next)
+            .or(self.running.first())
+            .map(|x| x.id)
+        else {
+            return Ok(());
+        };
+        if self.active == Some(id) || !self.suspend(screen).await? {
+            return Ok(());
+        }
+
+        let index = self.find(id).unwrap();
+        self.resume(screen, index).await
+    }
+
+    /// Checks what happened after calling into the module on screen. It's
+    /// closed if it failed, or asked to exit and didn't change its mind in
+    /// [`Module::on_exit`]. Losing the connection isn't the module's fault,
+    /// so it's kept open to carry on once reconnected.
+    async fn after_call(&mut self, screen: &mut State, result: Result<()>) -> Result<()> {
+        if screen.disconnected() {
+            return result;
+        }
+
+        let Some(index) = self.active.and_then(|x| self.find(x)) else {
+            return result;
+        };
+
+        if let Err(err) = result {
+            return self.fail(screen, index, err).await;
+        }
+
+        if !screen.take_exit() {
+            return Ok(());
+        }
+
+        match catch(self.running[index].module.on_exit(screen)).await {
+            Ok(true) => {
+                self.close(screen, index);
+                self.draw(screen).await
+            }
+            Ok(false) => {
+                info!("{} didn't exit", self.name(index));
+                Ok(())
+            }
+            Err(err) if screen.disconnected() => Err(err),
+            Err(err) => self.fail(screen, index, err).await,
+        }
+    }
+
+    /// Runs a timer callback for a module in the background against its own
+    /// copy of the screen. If it fails, the error is shown over whatever is
+    /// on screen.
+    async fn background(
+        &mut self,
+        screen: &mut State,
+        index: usize,
+        event: TimerEvent,
+    ) -> Result<()> {
+        let shown = Box::new(*screen.chars());
+        let running = &mut self.running[index];
+        screen.restore(&running.chars);
+        screen.set_hidden(true);
+
+        let mut result = catch(running.module.callback(screen, event)).await;
+        let mut exited = false;
+        if result.is_ok() && screen.take_exit() {
+            result = catch(running.module.on_exit(screen))
+                .await
+                .map(|x| exited = x);
+        }
+
+        screen.set_hidden(false);
+        *running.chars = *screen.chars();
+        screen.restore(&shown);
+
+        if screen.disconnected() {
+            return result;
+        }
+
+        match result {
+            Ok(()) if !exited => return Ok(()),
+            Ok(()) => self.close(screen, index),
+            Err(err) => {
+                let name = self.name(index).to_owned();
+                error!("{name} failed: {err:#}");
+                self.close(screen, index);
+                show_error(screen, &name, &err).await?;
+            }
+        }
+
+        if self.active.is_none() {
+            self.draw(screen).await?;
+        }
+        Ok(())
+    }
+
+    /// Shows why the module on screen failed and closes it.
+    async fn fail(&mut self, screen: &mut State, index: usize, err: anyhow::Error) -> Result<()> {
+        let name = self.name(index).to_owned();
+        error!("{name} failed: {err:#}");
+        show_error(screen, &name, &err).await?;
+        self.close(screen, index);
+        self.draw(screen).await
+    }
+
+    /// Drops a running module along with its timers.
+    fn close(&mut self, screen: &mut State, index: usize) {
+        info!("Closed {}", self.name(index));
+        let running = self.running.remove(index);
+        if self.active == Some(running.id) {
+            self.active = None;
+        }
+
+        screen.set_owner(running.id);
+        screen.take_exit();
+        screen.cancel_all();
+        screen.set_owner(0);
+        self.update_list();
+    }
+
+    /// Gives each running module a chance to clean up before the bridge
+    /// exits. Returns false if one of them wants to stay, in which case it's
+    /// brought back to the screen.
+    async fn exit_running(&mut self, screen: &mut State) -> Result<bool> {
+        while let Some(running) = self.running.first_mut() {
+            // Show the module's screen in case it asks something
+            screen.set_owner(running.id);
+            screen.restore(&running.chars);
+            screen.draw().await?;
+
+            match catch(running.module.on_exit(screen)).await {
+                Ok(true) => self.close(screen, 0),
+                Ok(false) => {
+                    self.active = Some(self.running[0].id);
+                    self.list.select(self.running[0].entry);
+                    return Ok(false);
+                }
+                Err(err) if screen.disconnected() => return Err(err),
+                Err(err) => {
+                    error!("{} failed: {err:#}", self.name(0));
+                    self.close(screen, 0);
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Runs every running module's exit hook before the bridge stops. They
+    /// can't stay, so a refusal is ignored and hooks that take too long are
+    /// cut short.
+    pub async fn shutdown(&mut self, screen: &mut State) {
+        while !self.running.is_empty() {
+            self.force_exit(screen, 0).await;
+            self.close(screen, 0);
+        }
+    }
+
+    /// Runs the exit hook of a module that has to go whatever it answers,
+    /// in the background and cut short if it waits for a key.
+    async fn force_exit(&mut self, screen: &mut State, index: usize) {
+        let shown = Box::new(*screen.chars());
+        let running = &mut self.running[index];
+        screen.set_owner(running.id);
+        screen.restore(&running.chars);
+        screen.set_hidden(true);
+
+        let exit = catch(running.module.on_exit(screen));
+        let result = time::timeout(EXIT_TIMEOUT, exit).await;
+        screen.set_hidden(false);
+        screen.restore(&shown);
+
+        match result {
+            Ok(Ok(_)) => {}
+            Ok(Err(err)) => error!("{} failed: {err:#}", self.name(index)),
+            Err(_) => warn!("{} took too long to exit", self.name(index)),
+        }
+    }
 }
 
 #[async_trait]
 impl Module for Menu {
     async fn init(&mut self, screen: &mut State) -> Result<()> {
+        screen.set_keymap(self.keymap.clone());
         self.draw(screen).await?;
         Ok(())
     }
 
-    async fn on_key(&mut self, screen: &mut State, key: u8) -> Result<()> {
-        if key == 0x12 {
-            screen.redraw().await?;
-            return Ok(());
+    async fn on_key(&mut self, screen: &mut State, key: KeyEvent) -> Result<()> {
+        let active = self.active.and_then(|x| self.find(x));
+        let raw = active.is_some_and(|x| self.running[x].module.raw_input());
+        let action = self.keymap.action(key).filter(|_| !raw);
+        if let Some(action) = action.filter(|x| !self.passthrough().contains(x)) {
+            return self.run_action(screen, action).await;
+        }
+
+        if let Some(index) = active {
+            let running = &mut self.running[index];
+            screen.set_owner(running.id);
+            let result = catch(running.module.on_key(screen, key)).await;
+            return self.after_call(screen, result).await;
         }
 
-        if let Some(module) = &mut self.module {
-            module.on_key(screen, key).await?;
+        match self.list.on_key(key) {
+            Select::Chosen(i) if i < self.entries.len() => self.open(screen, i).await,
+            Select::Chosen(_) => {
+                if !self.exit_running(screen).await? {
+                    return Ok(());
+                }
 
-            if screen.take_exit() {
+                info!("Exiting from the menu");
                 screen.clear();
-                screen.unschedule(None);
-                self.draw(screen).await?;
-                self.module = None;
+                screen.draw().await?;
+                screen.exit();
+                Ok(())
             }
+            Select::Moved | Select::Ignored => self.draw(screen).await,
+        }
+    }
+
+    /// Routes the timer to the module that scheduled it, which may be in the
+    /// background.
+    async fn callback(&mut self, screen: &mut State, event: TimerEvent) -> Result<()> {
+        let Some(index) = self.find(screen.owner()) else {
             return Ok(());
+        };
+
+        if self.active != Some(screen.owner()) {
+            return self.background(screen, index, event).await;
         }
 
-        match key {
-            0x1E => self.selection = self.selection.saturating_sub(1),
-            0x1F => self.selection += 1,
-            0x0D => {
-                let mut module: Box<dyn Module + Send> = match self.selection {
-                    0 => Box::new(ChatGptModule::default()),
-                    1 => Box::new(PrinterModule::default()),
-                    2 => Box::new(KeyboardModule::default()),
-                    _ => unreachable!(),
-                };
+        let result = catch(self.running[index].module.callback(screen, event)).await;
+        self.after_call(screen, result).await
+    }
+}
 
-                screen.clear();
-                module.init(screen).await?;
-                self.module = Some(module);
-                return Ok(());
-            }
-            _ => {}
-        };
-        self.draw(screen).await?;
+impl Default for Menu {
+    fn default() -> Self {
+        Self::new(Registry::default(), Vec::new()).unwrap()
+    }
+}
 
-        Ok(())
+impl Entry {
+    /// Whether both are for the same module, so one running under `other`
+    /// can carry on under this one.
+    fn same(&self, other: &Entry) -> bool {
+        self.config.kind == other.config.kind && self.name() == other.name()
     }
 
-    async fn callback(&mut self, screen: &mut State, kind: u32) -> Result<()> {
-        if let Some(module) = &mut self.module {
-            module.callback(screen, kind).await?;
-        }
+    fn name(&self) -> &str {
+        self.config
+            .name
+            .as_deref()
+            .unwrap_or(self.registration.name)
+    }
+}
 
-        Ok(())
+async fn show_error(screen: &mut State, name: &str, err: &anyhow::Error) -> Result<()> {
+    let message = format!("{name}: {err:#}");
+    dialog::alert(screen, &charset::encode_text(&message)).await
+}
+
+/// Turns a panic in a module into an error, so it can be reported like any
+/// other failure without taking down the bridge.
+async fn catch<T>(future: impl Future<Output = Result<T>>) -> Result<T> {
+    match AssertUnwindSafe(future).catch_unwind().await {
+        Ok(result) => result,
+        Err(panic) => Err(anyhow!("Panicked: {}", panic_message(&*panic))),
+    }
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown cause"
     }
 }
@@ -1,19 +1,49 @@
 use anyhow::Result;
 use async_trait::async_trait;
 
-use crate::state::State;
+use crate::{key::KeyEvent, state::State, timer::TimerEvent};
 
 pub mod chatgpt;
 pub mod keyboard;
 pub mod menu;
 pub mod printer;
+pub mod registry;
 
 #[async_trait]
 pub trait Module {
     async fn init(&mut self, screen: &mut State) -> Result<()>;
-    async fn on_key(&mut self, screen: &mut State, key: u8) -> Result<()>;
-    async fn callback(&mut self, screen: &mut State, kind: u32) -> Result<()> {
-        let _ = (screen, kind);
+    async fn on_key(&mut self, screen: &mut State, key: KeyEvent) -> Result<()>;
+
+    /// Whether every key should reach the module as it is, skipping the
+    /// global shortcuts, like while it's receiving a file.
+    fn raw_input(&self) -> bool {
+        false
+    }
+
+    async fn callback(&mut self, screen: &mut State, event: TimerEvent) -> Result<()> {
+        let _ = (screen, event);
+        Ok(())
+    }
+
+    /// Called after the module asked to exit with [`State::exit`]. Returning
+    /// false keeps it open, e.g. when the user changed their mind.
+    async fn on_exit(&mut self, screen: &mut State) -> Result<bool> {
+        let _ = screen;
+        Ok(true)
+    }
+
+    /// Called before the module is sent to the background. Its screen is
+    /// kept as it is after this returns, and its timers keep firing with
+    /// [`State::hidden`] set.
+    async fn on_suspend(&mut self, screen: &mut State) -> Result<()> {
+        let _ = screen;
+        Ok(())
+    }
+
+    /// Called once the module's screen is back after being in the
+    /// background, to catch up on what happened meanwhile.
+    async fn on_resume(&mut self, screen: &mut State) -> Result<()> {
+        let _ = screen;
         Ok(())
     }
 }
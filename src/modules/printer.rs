@@ -1,25 +1,53 @@
 use std::{mem, time::Duration};
 
-use anyhow::Result;
+use anyhow::{Context, Result, anyhow};
 use async_trait::async_trait;
 use markdown2pdf::config::ConfigSource;
 use nalgebra::Vector2;
 use printers::{
-    common::base::{job::PrinterJobOptions, printer::Printer},
+    common::base::{
+        job::PrinterJobOptions,
+        printer::{Printer, PrinterState},
+    },
     get_printers,
 };
+use serde::Deserialize;
 use tokio::time::Instant;
 
-use crate::{modules::Module, state::State};
+use crate::{
+    config::ModuleConfig,
+    key::{KeyCode, KeyEvent},
+    modules::{Module, registry::Registration},
+    screen::{Screen, charset},
+    state::State,
+    timer::{Timer, TimerEvent},
+    widgets::{
+        dialog::{self, Progress},
+        list::{Item, List, Select},
+    },
+};
+
+/// Settings from the module's table in the config file.
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Settings {
+    /// Printer selected at first, by name.
+    printer: Option<String>,
+}
+
+/// How often the upload is checked for having finished, and the print job
+/// for its status.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 pub struct PrinterModule {
     printers: Vec<Printer>,
     state: StateMachine,
+    poll: Option<Timer>,
 }
 
 enum StateMachine {
     SelectPrinter {
-        selected: usize,
+        list: List,
     },
     Uploading {
         printer: usize,
@@ -31,7 +59,10 @@ enum StateMachine {
     Printing {
         printer: usize,
         job_id: u64,
+        progress: Progress,
     },
+    /// The job finished, shown until a key is pressed.
+    Done,
 }
 
 #[async_trait]
@@ -42,32 +73,44 @@ impl Module for PrinterModule {
         Ok(())
     }
 
-    async fn on_key(&mut self, screen: &mut State, key: u8) -> Result<()> {
-        if key == 0x1B {
+    async fn on_key(&mut self, screen: &mut State, key: KeyEvent) -> Result<()> {
+        // Once the file is coming in ESC is part of it, the upload ends when
+        // nothing more arrives
+        let receiving =
+            matches!(&self.state, StateMachine::Uploading { file, .. } if !file.is_empty());
+        if key.code == KeyCode::Esc && !receiving {
             screen.exit();
             return Ok(());
         }
 
         match &mut self.state {
-            StateMachine::SelectPrinter { selected } => match key {
-                0x1D => *selected = selected.saturating_sub(1),
-                0x1C => *selected = (*selected + 1).min(self.printers.len() - 1),
-                0x0D => {
+            StateMachine::SelectPrinter { list } => {
+                if let Select::Chosen(printer) = list.on_key(key) {
                     self.state = StateMachine::Uploading {
-                        printer: *selected,
+                        printer,
                         file: Vec::new(),
                         last_size: 0,
                         last_update: Instant::now(),
-                    }
+                    };
+
+                    let message = format!("Printer: {}", self.printers[printer].name);
+                    screen.clear();
+                    screen.write_string(Vector2::zeros(), &charset::encode_str(&message));
                 }
-                _ => {}
-            },
+            }
             StateMachine::Uploading { file, .. } => {
+                let Some(byte) = key.encode() else {
+                    return Ok(());
+                };
                 if file.is_empty() {
-                    screen.schedule(Duration::from_millis(250), 0);
+                    self.poll = Some(screen.repeat(POLL_INTERVAL, ()));
                 }
 
-                file.push(key);
+                file.push(byte);
+            }
+            StateMachine::Done => {
+                screen.exit();
+                return Ok(());
             }
             _ => {}
         }
@@ -77,8 +120,13 @@ impl Module for PrinterModule {
         Ok(())
     }
 
-    async fn callback(&mut self, screen: &mut State, kind: u32) -> Result<()> {
-        if kind != 0 {
+    /// The uploaded file comes in as keys, and may contain any byte.
+    fn raw_input(&self) -> bool {
+        matches!(self.state, StateMachine::Uploading { .. })
+    }
+
+    async fn callback(&mut self, screen: &mut State, event: TimerEvent) -> Result<()> {
+        if self.poll != Some(event.timer) {
             return Ok(());
         }
 
@@ -89,57 +137,85 @@ impl Module for PrinterModule {
                 last_size,
                 ..
             } => {
-                if mem::replace(last_size, file.len()) == file.len() {
-                    for byte in file.iter_mut() {
-                        (*byte == b'\r').then(|| *byte = b'\n');
-                    }
+                // Still receiving
+                if mem::replace(last_size, file.len()) != file.len() {
+                    return Ok(());
+                }
 
-                    let pdf = markdown2pdf::parse_into_bytes(
-                        String::from_utf8_lossy(file).into_owned(),
-                        ConfigSource::Default,
-                        None,
-                    )?;
-
-                    let job_id = self.printers[*printer]
-                        .print(&pdf, PrinterJobOptions::none())
-                        .unwrap();
-                    self.state = StateMachine::Printing {
-                        printer: *printer,
-                        job_id,
-                    };
-                    self.draw(screen).await?;
+                for byte in file.iter_mut() {
+                    (*byte == b'\r').then(|| *byte = b'\n');
                 }
-                screen.schedule(Duration::from_millis(250), 0);
+
+                let pdf = markdown2pdf::parse_into_bytes(
+                    charset::decode_bytes(file),
+                    ConfigSource::Default,
+                    None,
+                )?;
+
+                let job_id = self.printers[*printer]
+                    .print(&pdf, PrinterJobOptions::none())
+                    .map_err(|x| anyhow!("Failed to print: {x}"))?;
+                self.state = StateMachine::Printing {
+                    printer: *printer,
+                    job_id,
+                    progress: Progress::show(screen, b"Upload complete, printing").await?,
+                };
             }
-            StateMachine::Printing { printer, job_id } => {
+            StateMachine::Printing {
+                printer,
+                job_id,
+                progress,
+            } => {
                 let jobs = self.printers[*printer].get_active_jobs();
 
-                // todo: dont edit screen directly in callback
                 let Some(job) = jobs.iter().find(|x| x.id == *job_id) else {
-                    screen.write_string(Vector2::new(0, 3), b"Print successful");
-                    screen.draw().await?;
+                    // Left up rather than in a dialog, which would hold up
+                    // every other module's timers until a key is pressed
+                    progress.set_message(b"Print successful, press any key");
+                    progress.set(screen, Some(1.0)).await?;
+                    self.state = StateMachine::Done;
+
+                    if let Some(poll) = self.poll.take() {
+                        screen.cancel(poll);
+                    }
                     return Ok(());
                 };
 
-                let message = format!("Print Status: {:?}", job.state);
-                screen.write_string(Vector2::new(0, 3), message.as_bytes());
-                screen.draw().await?;
-                screen.schedule(Duration::from_millis(250), 0);
+                let message = format!("Print status: {:?}", job.state);
+                progress.set_message(message.as_bytes());
+                progress.set(screen, None).await?;
             }
             _ => {}
         }
 
         Ok(())
     }
+
+    async fn on_exit(&mut self, screen: &mut State) -> Result<bool> {
+        match &self.state {
+            StateMachine::Uploading { file, .. } if !file.is_empty() => {
+                dialog::confirm(screen, b"Cancel upload?").await
+            }
+            _ => Ok(true),
+        }
+    }
 }
 
 impl PrinterModule {
     async fn draw(&mut self, screen: &mut State) -> Result<()> {
         match &mut self.state {
-            StateMachine::SelectPrinter { selected } => {
-                let message = format!("Printer: {}", self.printers[*selected].name);
-                screen.rect(Vector2::new(0, 1), Vector2::new(40, 1), b' '.into());
-                screen.write_string(Vector2::new(0, 1), message.as_bytes());
+            StateMachine::SelectPrinter { list } => {
+                screen.clear();
+                if list.items().is_empty() {
+                    screen.write_string(Vector2::zeros(), b"No printers found");
+                } else {
+                    screen.write_string(Vector2::zeros(), b"Select a printer:");
+                    let mut window = screen.window(
+                        Vector2::new(0, 1),
+                        Vector2::new(Screen::WIDTH, Screen::HEIGHT - 1),
+                    );
+                    list.draw(&mut window);
+                }
                 screen.draw().await?;
             }
             StateMachine::Uploading {
@@ -156,20 +232,50 @@ impl PrinterModule {
                     screen.draw().await?;
                 }
             }
-            StateMachine::Printing { .. } => {
-                screen.write_string(Vector2::new(0, 2), b"Upload complete!");
-                screen.draw().await?;
-            }
+            // Shown by the progress dialog
+            StateMachine::Printing { .. } | StateMachine::Done => {}
         }
         Ok(())
     }
 }
 
-impl Default for PrinterModule {
-    fn default() -> Self {
-        Self {
-            printers: get_printers(),
-            state: StateMachine::SelectPrinter { selected: 0 },
+impl PrinterModule {
+    pub const REGISTRATION: Registration = Registration {
+        kind: "printer",
+        name: "Printer",
+        description: "Print a file uploaded from TELCOM",
+        hotkey: None,
+        new: |config| Ok(Box::new(Self::new(config)?)),
+    };
+
+    pub fn new(config: &ModuleConfig) -> Result<Self> {
+        Self::with_printers(config, get_printers())
+    }
+
+    /// Offers `printers` instead of the ones on the system.
+    pub fn with_printers(config: &ModuleConfig, printers: Vec<Printer>) -> Result<Self> {
+        let settings: Settings = config.settings()?;
+
+        let items = printers
+            .iter()
+            .map(|x| {
+                let name = charset::encode_str(&x.name);
+                Item::new(&name).enabled(!matches!(x.state, PrinterState::OFFLINE))
+            })
+            .collect();
+
+        let mut list = List::new(items);
+        if let Some(name) = settings.printer {
+            let index = printers
+                .iter()
+                .position(|x| x.name == name || x.system_name == name);
+            list.select(index.with_context(|| format!("No printer named `{name}`"))?);
         }
+
+        Ok(Self {
+            printers,
+            state: StateMachine::SelectPrinter { list },
+            poll: None,
+        })
     }
 }
@@ -0,0 +1,63 @@
+use anyhow::{Result, bail};
+
+use crate::{
+    config::ModuleConfig,
+    modules::{Module, chatgpt::ChatGptModule, keyboard::KeyboardModule, printer::PrinterModule},
+};
+
+/// Describes a module that can be added to the menu, and how to create it.
+#[derive(Clone, Copy)]
+pub struct Registration {
+    /// Used in the config file to refer to the module.
+    pub kind: &'static str,
+    /// Shown in the menu unless the config gives another name.
+    pub name: &'static str,
+    /// Shown below the menu while the module is selected.
+    pub description: &'static str,
+    /// Opens the module from the menu, instead of the first letter of its name.
+    pub hotkey: Option<u8>,
+    pub new: fn(&ModuleConfig) -> Result<Box<dyn Module + Send>>,
+}
+
+/// Every module the menu can open. [`Registry::default`] has the built in
+/// ones, more can be added with [`Registry::register`].
+#[derive(Clone)]
+pub struct Registry {
+    modules: Vec<Registration>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self {
+            modules: Vec::new(),
+        }
+    }
+
+    /// Adds a module, replacing any registered with the same kind.
+    pub fn register(&mut self, registration: Registration) {
+        self.modules.retain(|x| x.kind != registration.kind);
+        self.modules.push(registration);
+    }
+
+    pub fn get(&self, kind: &str) -> Result<&Registration> {
+        match self.modules.iter().find(|x| x.kind == kind) {
+            Some(registration) => Ok(registration),
+            None => bail!("Unknown module `{kind}`"),
+        }
+    }
+
+    /// Registered modules, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &Registration> {
+        self.modules.iter()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(ChatGptModule::REGISTRATION);
+        registry.register(PrinterModule::REGISTRATION);
+        registry.register(KeyboardModule::REGISTRATION);
+        registry
+    }
+}
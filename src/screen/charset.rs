@@ -0,0 +1,115 @@
+//! Conversion between Unicode and the Model 100's 8-bit character set. The
+//! lower half is ASCII. Of the upper half only the graphics the original
+//! screens were drawn with on a real Model 100 are mapped, the rest isn't
+//! checked against the machine yet and is sent as a similar ASCII character
+//! instead.
+
+/// Characters from the upper half known to display as their Unicode match.
+#[rustfmt::skip]
+const EXTENDED: &[(u8, char)] = &[
+    (0xE9, '█'),
+    (0xF0, '┌'), (0xF1, '─'), (0xF2, '┐'), (0xF4, '├'), (0xF5, '│'),
+    (0xF6, '└'), (0xF7, '┘'), (0xF9, '┤'),
+];
+
+/// Replacements for characters missing from the character set, tried when
+/// there's no direct match.
+#[rustfmt::skip]
+const FALLBACK: &[(char, &str)] = &[
+    ('‘', "'"), ('’', "'"), ('‚', "'"), ('′', "'"),
+    ('“', "\""), ('”', "\""), ('„', "\""), ('″', "\""),
+    ('–', "-"), ('—', "-"), ('‐', "-"), ('−', "-"),
+    ('…', "..."), ('•', "*"), ('·', "."), ('\u{a0}', " "),
+    ('€', "EUR"), ('™', "TM"), ('⇒', "=>"), ('⇐', "<="),
+    ('━', "─"), ('═', "─"), ('┃', "│"), ('║', "│"),
+    ('╔', "┌"), ('┏', "┌"), ('╗', "┐"), ('┓', "┐"),
+    ('╚', "└"), ('┗', "└"), ('╝', "┘"), ('┛', "┘"),
+    ('╠', "├"), ('┣', "├"), ('╣', "┤"), ('┫', "┤"),
+    ('╦', "─"), ('┳', "─"), ('╩', "─"), ('┻', "─"),
+    ('╬', "│"), ('╋', "│"), ('╭', "┌"), ('╮', "┐"),
+    ('╰', "└"), ('╯', "┘"), ('┬', "─"), ('┴', "─"), ('┼', "│"),
+    ('À', "A"), ('Á', "A"), ('Â', "A"), ('Ã', "A"), ('Ä', "A"), ('Å', "A"),
+    ('Æ', "AE"), ('Ç', "C"), ('È', "E"), ('É', "E"), ('Ê', "E"), ('Ë', "E"),
+    ('Ì', "I"), ('Í', "I"), ('Î', "I"), ('Ï', "I"), ('Ñ', "N"), ('Ò', "O"),
+    ('Ó', "O"), ('Ô', "O"), ('Õ', "O"), ('Ö', "O"), ('Ø', "O"), ('Ù', "U"),
+    ('Ú', "U"), ('Û', "U"), ('Ü', "U"), ('Ý', "Y"), ('ß', "ss"),
+    ('à', "a"), ('á', "a"), ('â', "a"), ('ã', "a"), ('ä', "a"), ('å', "a"),
+    ('æ', "ae"), ('ç', "c"), ('è', "e"), ('é', "e"), ('ê', "e"), ('ë', "e"),
+    ('ì', "i"), ('í', "i"), ('î', "i"), ('ï', "i"), ('ñ', "n"), ('ò', "o"),
+    ('ó', "o"), ('ô', "o"), ('õ', "o"), ('ö', "o"), ('ø', "o"), ('ù', "u"),
+    ('ú', "u"), ('û', "u"), ('ü', "u"), ('ý', "y"), ('ÿ', "y"),
+    ('×', "x"), ('÷', "/"), ('±', "+/-"), ('«', "<<"), ('»', ">>"),
+    ('¿', "?"), ('¡', "!"), ('©', "(C)"), ('®', "(R)"),
+    ('ā', "a"), ('ă', "a"), ('ą', "a"), ('ć', "c"), ('č', "c"), ('ď', "d"),
+    ('ē', "e"), ('ę', "e"), ('ě', "e"), ('ğ', "g"), ('ī', "i"), ('ı', "i"),
+    ('ł', "l"), ('ń', "n"), ('ň', "n"), ('ō', "o"), ('ő', "o"), ('œ', "oe"),
+    ('ř', "r"), ('ś', "s"), ('š', "s"), ('ş', "s"), ('ť', "t"), ('ū', "u"),
+    ('ů', "u"), ('ű', "u"), ('ý', "y"), ('ź', "z"), ('ž', "z"), ('ż', "z"),
+    ('Ā', "A"), ('Ą', "A"), ('Ć', "C"), ('Č', "C"), ('Ď', "D"), ('Ē', "E"),
+    ('Ę', "E"), ('Ě', "E"), ('Ğ', "G"), ('İ', "I"), ('Ł', "L"), ('Ń', "N"),
+    ('Ň', "N"), ('Ō', "O"), ('Ő', "O"), ('Œ', "OE"), ('Ř', "R"), ('Ś', "S"),
+    ('Š', "S"), ('Ş', "S"), ('Ť', "T"), ('Ū', "U"), ('Ů', "U"), ('Ű', "U"),
+    ('Ý', "Y"), ('Ÿ', "Y"), ('Ź', "Z"), ('Ž', "Z"), ('Ż', "Z"),
+];
+
+/// Converts a character to its closest single Model 100 character, or `?`.
+pub fn encode(chr: char) -> u8 {
+    direct(chr)
+        .or_else(|| {
+            fallback(chr)
+                .and_then(|x| x.chars().next())
+                .and_then(direct)
+        })
+        .unwrap_or(b'?')
+}
+
+/// Converts text to Model 100 characters, replacing missing characters with
+/// one or more similar ones where possible.
+pub fn encode_str(str: &str) -> Vec<u8> {
+    let mut out = Vec::with_capacity(str.len());
+    for chr in str.chars() {
+        match (direct(chr), fallback(chr)) {
+            (Some(byte), _) => out.push(byte),
+            (None, Some(fallback)) => out.extend(fallback.chars().map(encode)),
+            (None, None) => out.push(b'?'),
+        }
+    }
+    out
+}
+
+/// Like [`encode_str`], but keeps line breaks as `\n` for text that's laid
+/// out with [`Wrap`](super::Wrap).
+pub fn encode_text(str: &str) -> Vec<u8> {
+    let lines = str.split('\n').map(|x| x.strip_suffix('\r').unwrap_or(x));
+    lines.map(encode_str).collect::<Vec<_>>().join(&b'\n')
+}
+
+/// Converts a Model 100 character to Unicode, or `�` for ones that aren't
+/// mapped.
+pub fn decode(byte: u8) -> char {
+    match byte {
+        0x00..=0x7F => byte as char,
+        _ => (EXTENDED.iter().find(|x| x.0 == byte)).map_or(char::REPLACEMENT_CHARACTER, |x| x.1),
+    }
+}
+
+/// Converts text received from the Model 100 to a Unicode string.
+pub fn decode_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|&x| decode(x)).collect()
+}
+
+fn direct(chr: char) -> Option<u8> {
+    // Control characters would be sent to the terminal as commands
+    match chr {
+        '\t' => return Some(b' '),
+        '\0'..='\x1F' | '\x7F' => return Some(b'?'),
+        _ if chr.is_ascii() => return Some(chr as u8),
+        _ => {}
+    }
+
+    EXTENDED.iter().find(|x| x.1 == chr).map(|x| x.0)
+}
+
+fn fallback(chr: char) -> Option<&'static str> {
+    FALLBACK.iter().find(|x| x.0 == chr).map(|x| x.1)
+}
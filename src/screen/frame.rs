@@ -0,0 +1,170 @@
+use nalgebra::Vector2;
+
+use super::{Screen, Window, glyph};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameStyle {
+    /// Thin lines from the line graphics characters.
+    #[default]
+    Single,
+    /// Heavy borders made of solid blocks, the Model 100 has no double line
+    /// glyphs.
+    Double,
+}
+
+/// The glyphs needed to draw a frame in some style.
+struct Pieces {
+    top_left: u8,
+    top_right: u8,
+    bottom_left: u8,
+    bottom_right: u8,
+    top: u8,
+    bottom: u8,
+    left: u8,
+    right: u8,
+    tee_left: u8,
+    tee_right: u8,
+    divider: u8,
+}
+
+impl FrameStyle {
+    fn pieces(self) -> Pieces {
+        match self {
+            FrameStyle::Single => Pieces {
+                top_left: glyph::TOP_LEFT,
+                top_right: glyph::TOP_RIGHT,
+                bottom_left: glyph::BOTTOM_LEFT,
+                bottom_right: glyph::BOTTOM_RIGHT,
+                top: glyph::HORIZONTAL,
+                bottom: glyph::HORIZONTAL,
+                left: glyph::VERTICAL,
+                right: glyph::VERTICAL,
+                tee_left: glyph::TEE_RIGHT,
+                tee_right: glyph::TEE_LEFT,
+                divider: glyph::HORIZONTAL,
+            },
+            FrameStyle::Double => Pieces {
+                top_left: glyph::FULL_BLOCK,
+                top_right: glyph::FULL_BLOCK,
+                bottom_left: glyph::FULL_BLOCK,
+                bottom_right: glyph::FULL_BLOCK,
+                top: glyph::FULL_BLOCK,
+                bottom: glyph::FULL_BLOCK,
+                left: glyph::FULL_BLOCK,
+                right: glyph::FULL_BLOCK,
+                tee_left: glyph::FULL_BLOCK,
+                tee_right: glyph::FULL_BLOCK,
+                divider: glyph::FULL_BLOCK,
+            },
+        }
+    }
+}
+
+impl Window<'_> {
+    /// Draws a border around the given area and returns the window inside it.
+    pub fn frame(
+        &mut self,
+        pos: Vector2<usize>,
+        size: Vector2<usize>,
+        style: FrameStyle,
+    ) -> Window<'_> {
+        self.draw_frame(pos, size, style, b"");
+        self.window(pos + Vector2::repeat(1), size.map(|x| x.saturating_sub(2)))
+    }
+
+    /// Like [`Window::frame`], with a title centered in the top edge.
+    pub fn frame_titled(
+        &mut self,
+        pos: Vector2<usize>,
+        size: Vector2<usize>,
+        style: FrameStyle,
+        title: &[u8],
+    ) -> Window<'_> {
+        self.draw_frame(pos, size, style, title);
+        self.window(pos + Vector2::repeat(1), size.map(|x| x.saturating_sub(2)))
+    }
+
+    /// Draws a horizontal line with tee junctions at both ends, meant to
+    /// split a frame of the same width.
+    pub fn divider(&mut self, pos: Vector2<usize>, width: usize, style: FrameStyle) {
+        if width < 2 {
+            return;
+        }
+
+        let pieces = style.pieces();
+        for x in 1..width - 1 {
+            self.put(pos + Vector2::x() * x, pieces.divider.into());
+        }
+
+        self.put(pos, pieces.tee_left.into());
+        self.put(pos + Vector2::x() * (width - 1), pieces.tee_right.into());
+    }
+
+    fn draw_frame(
+        &mut self,
+        pos: Vector2<usize>,
+        size: Vector2<usize>,
+        style: FrameStyle,
+        title: &[u8],
+    ) {
+        if size.x < 2 || size.y < 2 {
+            return;
+        }
+
+        let pieces = style.pieces();
+        let far = pos + size - Vector2::repeat(1);
+
+        for x in pos.x + 1..far.x {
+            self.put(Vector2::new(x, pos.y), pieces.top.into());
+            self.put(Vector2::new(x, far.y), pieces.bottom.into());
+        }
+
+        for y in pos.y + 1..far.y {
+            self.put(Vector2::new(pos.x, y), pieces.left.into());
+            self.put(Vector2::new(far.x, y), pieces.right.into());
+        }
+
+        self.put(pos, pieces.top_left.into());
+        self.put(Vector2::new(far.x, pos.y), pieces.top_right.into());
+        self.put(Vector2::new(pos.x, far.y), pieces.bottom_left.into());
+        self.put(far, pieces.bottom_right.into());
+
+        // Padded with a space on either side, keeping the corners visible
+        let title = &title[..title.len().min(size.x.saturating_sub(4))];
+        if !title.is_empty() {
+            let start = pos.x + (size.x - title.len() - 2) / 2;
+            self.put(Vector2::new(start, pos.y), b' '.into());
+            self.write_string(Vector2::new(start + 1, pos.y), title);
+            self.put(Vector2::new(start + title.len() + 1, pos.y), b' '.into());
+        }
+    }
+}
+
+impl Screen {
+    /// Draws a border around the given area and returns the window inside it.
+    pub fn frame(
+        &mut self,
+        pos: Vector2<usize>,
+        size: Vector2<usize>,
+        style: FrameStyle,
+    ) -> Window<'_> {
+        self.full().draw_frame(pos, size, style, b"");
+        self.window(pos + Vector2::repeat(1), size.map(|x| x.saturating_sub(2)))
+    }
+
+    /// Like [`Screen::frame`], with a title centered in the top edge.
+    pub fn frame_titled(
+        &mut self,
+        pos: Vector2<usize>,
+        size: Vector2<usize>,
+        style: FrameStyle,
+        title: &[u8],
+    ) -> Window<'_> {
+        self.full().draw_frame(pos, size, style, title);
+        self.window(pos + Vector2::repeat(1), size.map(|x| x.saturating_sub(2)))
+    }
+
+    pub fn divider(&mut self, pos: Vector2<usize>, width: usize, style: FrameStyle) {
+        self.full().divider(pos, width, style);
+    }
+}
@@ -0,0 +1,16 @@
+//! Named characters from the Model 100's graphics range.
+
+pub const TOP_LEFT: u8 = 0xF0;
+pub const HORIZONTAL: u8 = 0xF1;
+pub const TOP_RIGHT: u8 = 0xF2;
+pub const TEE_DOWN: u8 = 0xF3;
+pub const TEE_RIGHT: u8 = 0xF4;
+pub const VERTICAL: u8 = 0xF5;
+pub const BOTTOM_LEFT: u8 = 0xF6;
+pub const BOTTOM_RIGHT: u8 = 0xF7;
+pub const CROSS: u8 = 0xF8;
+pub const TEE_LEFT: u8 = 0xF9;
+pub const TEE_UP: u8 = 0xFA;
+
+/// Solid block, used as the cursor by the original ChatGPT screen.
+pub const FULL_BLOCK: u8 = 0xE9;
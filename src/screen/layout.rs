@@ -0,0 +1,150 @@
+use std::mem;
+
+/// Options for wrapping text into lines.
+#[derive(Clone, Copy)]
+pub struct Wrap {
+    pub width: usize,
+    /// Lines available, anything past this is dropped and reported as overflow.
+    pub height: Option<usize>,
+    /// Split words longer than the width across lines instead of cutting
+    /// them off.
+    pub break_words: bool,
+    /// Start a new line at each line break, otherwise they're treated as
+    /// spaces and the text is reflowed.
+    pub paragraphs: bool,
+    /// End the last line with `...` when text was cut off.
+    pub ellipsis: bool,
+}
+
+pub struct Wrapped {
+    pub lines: Vec<Vec<u8>>,
+    /// Whether some of the text didn't fit.
+    pub overflow: bool,
+}
+
+impl Wrap {
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            height: None,
+            break_words: true,
+            paragraphs: true,
+            ellipsis: false,
+        }
+    }
+
+    pub fn height(mut self, height: usize) -> Self {
+        self.height = Some(height);
+        self
+    }
+
+    pub fn break_words(mut self, break_words: bool) -> Self {
+        self.break_words = break_words;
+        self
+    }
+
+    pub fn paragraphs(mut self, paragraphs: bool) -> Self {
+        self.paragraphs = paragraphs;
+        self
+    }
+
+    pub fn ellipsis(mut self, ellipsis: bool) -> Self {
+        self.ellipsis = ellipsis;
+        self
+    }
+
+    pub fn wrap(&self, text: &[u8]) -> Wrapped {
+        let mut wrapped = Wrapped {
+            lines: Vec::new(),
+            overflow: false,
+        };
+
+        if self.width == 0 {
+            wrapped.overflow = !text.is_empty();
+            return wrapped;
+        }
+
+        let newline = |x: &u8| *x == b'\n' || *x == b'\r';
+        let paragraphs = if self.paragraphs {
+            split_lines(text)
+        } else {
+            vec![text]
+        };
+
+        for paragraph in paragraphs {
+            let mut line = Vec::new();
+            for word in paragraph.split(|x| *x == b' ' || newline(x)) {
+                if word.is_empty() {
+                    continue;
+                }
+
+                let space = !line.is_empty() as usize;
+                if line.len() + space + word.len() <= self.width {
+                    (space == 1).then(|| line.push(b' '));
+                    line.extend_from_slice(word);
+                    continue;
+                }
+
+                if !line.is_empty() {
+                    wrapped.lines.push(mem::take(&mut line));
+                }
+
+                if word.len() <= self.width {
+                    line.extend_from_slice(word);
+                } else if self.break_words {
+                    let mut chunks = word.chunks(self.width).peekable();
+                    while let Some(chunk) = chunks.next() {
+                        match chunks.peek() {
+                            Some(_) => wrapped.lines.push(chunk.to_vec()),
+                            None => line.extend_from_slice(chunk),
+                        }
+                    }
+                } else {
+                    wrapped.overflow = true;
+                    line.extend_from_slice(&word[..self.width]);
+                }
+            }
+
+            wrapped.lines.push(line);
+        }
+
+        if let Some(height) = self.height
+            && wrapped.lines.len() > height
+        {
+            wrapped.lines.truncate(height);
+            wrapped.overflow = true;
+        }
+
+        if self.ellipsis
+            && wrapped.overflow
+            && let Some(last) = wrapped.lines.last_mut()
+        {
+            last.truncate(self.width.saturating_sub(3));
+            last.extend_from_slice(&b"..."[..3.min(self.width)]);
+        }
+
+        wrapped
+    }
+}
+
+impl Wrapped {
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+/// Splits text at `\r\n`, `\n` or `\r`, ignoring one at the very end.
+fn split_lines(text: &[u8]) -> Vec<&[u8]> {
+    let mut lines = Vec::new();
+    let mut rest = text;
+    while let Some(i) = rest.iter().position(|x| *x == b'\n' || *x == b'\r') {
+        lines.push(&rest[..i]);
+        let len = if rest[i..].starts_with(b"\r\n") { 2 } else { 1 };
+        rest = &rest[i + len..];
+    }
+
+    if !rest.is_empty() || lines.is_empty() {
+        lines.push(rest);
+    }
+    lines
+}
@@ -0,0 +1,197 @@
+use nalgebra::Vector2;
+
+pub mod charset;
+mod frame;
+pub mod glyph;
+mod layout;
+mod render;
+mod window;
+
+pub use frame::FrameStyle;
+pub use layout::{Wrap, Wrapped};
+use render::Pen;
+pub use window::Window;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Char {
+    char: u8,
+    inverted: bool,
+}
+
+pub struct Screen {
+    previous: [Char; Self::WIDTH * Self::HEIGHT],
+    chars: [Char; Self::WIDTH * Self::HEIGHT],
+    pen: Pen,
+    stats: RenderStats,
+    scrolls: Vec<(usize, usize, isize)>,
+}
+
+/// Running totals of the bytes sent to the terminal by a [`Screen`].
+#[derive(Clone, Copy, Default, Debug)]
+pub struct RenderStats {
+    pub written: usize,
+    /// Bytes avoided compared to moving to each changed cell one step at a time.
+    pub saved: usize,
+}
+
+impl Char {
+    pub fn new(char: u8, inverted: bool) -> Self {
+        Self { char, inverted }
+    }
+
+    pub fn char(&self) -> u8 {
+        self.char
+    }
+
+    pub fn inverted(&self) -> bool {
+        self.inverted
+    }
+
+    pub fn invert(mut self) -> Self {
+        self.inverted ^= true;
+        self
+    }
+}
+
+impl Screen {
+    pub const WIDTH: usize = 40;
+    pub const HEIGHT: usize = 8;
+
+    pub const SIZE: usize = Self::WIDTH * Self::HEIGHT;
+
+    pub(crate) fn index(pos: Vector2<usize>) -> Option<usize> {
+        (pos.x < Self::WIDTH && pos.y < Self::HEIGHT).then(|| pos.x + pos.y * Self::WIDTH)
+    }
+
+    pub(crate) fn from_index(index: usize) -> Option<Vector2<usize>> {
+        (index < Self::SIZE).then(|| Vector2::new(index % Self::WIDTH, index / Self::WIDTH))
+    }
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Screen {
+            previous: [Char::default(); Self::SIZE],
+            pen: Pen::default(),
+            stats: RenderStats::default(),
+            scrolls: Vec::new(),
+
+            chars: [Char::default(); Self::SIZE],
+        }
+    }
+
+    pub fn chars(&self) -> &[Char; Self::SIZE] {
+        &self.chars
+    }
+
+    /// The screen as Unicode text, a line per row without trailing spaces.
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for row in self.chars.chunks(Self::WIDTH) {
+            let line: String = row.iter().map(|x| charset::decode(x.char)).collect();
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    pub fn put(&mut self, pos: Vector2<usize>, chr: Char) {
+        if let Some(index) = Screen::index(pos) {
+            self.chars[index] = chr;
+        }
+    }
+
+    /// Replaces every character, e.g. to put back what was under a dialog.
+    pub fn restore(&mut self, chars: &[Char; Self::SIZE]) {
+        self.chars = *chars;
+    }
+
+    pub fn clear(&mut self) {
+        self.chars.fill(Char::default());
+    }
+
+    fn full(&mut self) -> Window<'_> {
+        self.window(Vector2::zeros(), Vector2::new(Self::WIDTH, Self::HEIGHT))
+    }
+
+    pub fn write_string(&mut self, pos: Vector2<usize>, str: &[u8]) {
+        self.full().write_string(pos, str);
+    }
+
+    /// Writes Unicode text, converted with [`charset::encode_str`].
+    pub fn write_str(&mut self, pos: Vector2<usize>, str: &str) {
+        self.full().write_str(pos, str);
+    }
+
+    pub fn write_string_inverted(&mut self, pos: Vector2<usize>, str: &[u8], invert: bool) {
+        self.full().write_string_inverted(pos, str, invert);
+    }
+
+    pub fn write_string_wrapped(
+        &mut self,
+        pos: Vector2<usize>,
+        str: &[u8],
+        width: usize,
+    ) -> Wrapped {
+        self.window(pos, Vector2::new(width, Self::HEIGHT))
+            .write_string_wrapped(Vector2::zeros(), str)
+    }
+
+    /// Moves the contents of rows `top..=bottom` up by `lines`, or down if
+    /// negative, leaving blank rows to be filled in. The next draw shifts the
+    /// terminal's copy too instead of resending the whole region.
+    pub fn scroll_region(&mut self, top: usize, bottom: usize, lines: isize) {
+        let bottom = bottom.min(Self::HEIGHT - 1);
+        if top > bottom || lines == 0 {
+            return;
+        }
+
+        let lines = lines.clamp(
+            -(bottom as isize - top as isize + 1),
+            bottom as isize - top as isize + 1,
+        );
+        render::shift(&mut self.chars, top, bottom, lines);
+        self.scrolls.push((top, bottom, lines));
+    }
+
+    pub fn rect(&mut self, pos: Vector2<usize>, size: Vector2<usize>, chr: Char) {
+        self.full().rect(pos, size, chr);
+    }
+}
+
+impl From<&char> for Char {
+    fn from(value: &char) -> Self {
+        Self {
+            char: charset::encode(*value),
+            inverted: false,
+        }
+    }
+}
+
+impl From<u8> for Char {
+    fn from(value: u8) -> Self {
+        Self {
+            char: value,
+            inverted: false,
+        }
+    }
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for Char {
+    fn default() -> Self {
+        Self {
+            char: b' ',
+            inverted: false,
+        }
+    }
+}
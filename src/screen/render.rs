@@ -0,0 +1,328 @@
+use std::mem;
+
+use anyhow::Result;
+use nalgebra::Vector2;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::trace;
+
+use super::{Char, Screen};
+
+/// Where the terminal's cursor is and whether it's writing inverted text.
+#[derive(Clone, Copy, Default)]
+pub(super) struct Pen {
+    cursor: Vector2<usize>,
+    inverted: bool,
+}
+
+#[derive(Clone)]
+struct Plan {
+    out: Vec<u8>,
+    pen: Pen,
+}
+
+impl Screen {
+    pub async fn draw<T: AsyncWrite + Unpin>(&mut self, writer: &mut T) -> Result<()> {
+        let out = self.render(Vec::new());
+        send(writer, &out).await
+    }
+
+    pub async fn redraw<T: AsyncWrite + Unpin>(&mut self, writer: &mut T) -> Result<()> {
+        // Clear the screen and send everything that isn't blank
+        self.previous.fill(Char::default());
+        self.pen = Pen::default();
+
+        let out = self.render(b"\x0C\x1Bq".to_vec());
+        send(writer, &out).await
+    }
+
+    fn render(&mut self, mut out: Vec<u8>) -> Vec<u8> {
+        let naive = self.naive_cost();
+
+        let mut best = self.plan_frame(&self.previous, Plan::new(self.pen));
+        for (top, bottom, lines) in self.scroll_candidates() {
+            let mut previous = self.previous;
+            shift(&mut previous, top, bottom, lines);
+
+            let plan = self.plan_scroll(top, bottom, lines);
+            best = best.min(self.plan_frame(&previous, plan));
+        }
+
+        out.extend_from_slice(&best.out);
+        self.stats.written += best.out.len();
+        self.stats.saved += naive.saturating_sub(best.out.len());
+
+        self.pen = best.pen;
+        self.previous = self.chars;
+        out
+    }
+
+    /// Scrolls hinted with [`Screen::scroll_region`], or if there are none,
+    /// every way the whole screen could have moved.
+    fn scroll_candidates(&mut self) -> Vec<(usize, usize, isize)> {
+        let mut hints: Vec<(usize, usize, isize)> = Vec::new();
+        for (top, bottom, lines) in mem::take(&mut self.scrolls) {
+            match hints.last_mut() {
+                // Scrolling the same region again is sent as one bigger scroll
+                Some(last) if (last.0, last.1) == (top, bottom) && last.2 * lines > 0 => {
+                    let height = (bottom - top + 1) as isize;
+                    last.2 = (last.2 + lines).clamp(-height, height);
+                }
+                _ => hints.push((top, bottom, lines)),
+            }
+        }
+        if !hints.is_empty() {
+            return hints;
+        }
+
+        let changed_rows = (0..Self::HEIGHT)
+            .filter(|&y| Self::row(&self.chars, y) != Self::row(&self.previous, y))
+            .count();
+        if changed_rows < 2 {
+            return Vec::new();
+        }
+
+        let max = Self::HEIGHT as isize;
+        (1..max)
+            .flat_map(|x| [x, -x])
+            .map(|lines| (0, Self::HEIGHT - 1, lines))
+            .collect()
+    }
+
+    /// Shifts rows `top..=bottom` on the terminal with delete and insert line,
+    /// or by line feeding with scrolling enabled when that's cheaper.
+    fn plan_scroll(&self, top: usize, bottom: usize, lines: isize) -> Plan {
+        let n = lines.unsigned_abs();
+        let x = self.pen.cursor.x;
+
+        let repeat = |plan: &mut Plan, y: usize, seq: &[u8]| {
+            plan.pen.move_to(Vector2::new(x, y), &mut plan.out);
+            for _ in 0..n {
+                plan.out.extend_from_slice(seq);
+            }
+        };
+
+        let mut plan = Plan::new(self.pen);
+        let full = bottom == Self::HEIGHT - 1;
+        if lines > 0 {
+            repeat(&mut plan, top, b"\x1BM");
+            (!full).then(|| repeat(&mut plan, bottom + 1 - n, b"\x1BL"));
+        } else {
+            (!full).then(|| repeat(&mut plan, bottom + 1 - n, b"\x1BM"));
+            repeat(&mut plan, top, b"\x1BL");
+        }
+
+        if lines > 0 && top == 0 && full {
+            let mut native = Plan::new(self.pen);
+            native.out.extend_from_slice(b"\x1BW");
+            repeat(&mut native, bottom, b"\n");
+            native.out.extend_from_slice(b"\x1BV");
+            plan = plan.min(native);
+        }
+
+        plan
+    }
+
+    fn plan_frame(&self, previous: &[Char; Self::SIZE], mut plan: Plan) -> Plan {
+        for y in 0..Self::HEIGHT {
+            let row = self.plan_row(y, previous, plan.pen);
+            plan.out.extend_from_slice(&row.out);
+            plan.pen = row.pen;
+        }
+
+        plan
+    }
+
+    /// Finds the cheapest way to bring one row up to date. Unchanged cells
+    /// between changes are retyped when that beats moving the cursor, and
+    /// blank tails are erased with `ESC K` or `ESC l`.
+    fn plan_row(&self, y: usize, previous: &[Char; Self::SIZE], pen: Pen) -> Plan {
+        let row = Self::row(&self.chars, y);
+        let previous = Self::row(previous, y);
+
+        let changed = (0..Self::WIDTH)
+            .filter(|&x| row[x] != previous[x])
+            .collect::<Vec<_>>();
+        let mut best = self.plan_cells(y, &changed, pen);
+        if changed.is_empty() {
+            return best;
+        }
+
+        let tail = (row.iter().rposition(|x| *x != Char::default())).map_or(0, |x| x + 1);
+        if let Some(split) = changed.iter().position(|&x| x >= tail) {
+            let mut plan = self.plan_cells(y, &changed[..split], pen);
+            plan.pen
+                .move_to(Vector2::new(changed[split], y), &mut plan.out);
+            plan.out.extend_from_slice(b"\x1BK");
+            best = best.min(plan);
+        }
+
+        if tail == 0 {
+            let mut plan = Plan::new(pen);
+            plan.pen
+                .move_to(Vector2::new(pen.cursor.x, y), &mut plan.out);
+            plan.out.extend_from_slice(b"\x1Bl");
+            best = best.min(plan);
+        }
+
+        best
+    }
+
+    fn plan_cells(&self, y: usize, cells: &[usize], pen: Pen) -> Plan {
+        let row = Self::row(&self.chars, y);
+        let mut plan = Plan::new(pen);
+
+        for &x in cells {
+            let pos = Vector2::new(x, y);
+            if plan.pen.cursor.y != y || plan.pen.cursor.x > x {
+                plan.pen.move_to(pos, &mut plan.out);
+                plan.pen.write(row[x], &mut plan.out);
+                continue;
+            }
+
+            // Compared after writing the cell, since retyping the gap can
+            // change whether it needs inverting. Both options then leave the
+            // pen in the same state, so the cheaper one is the right choice.
+            let mut jump = plan.clone();
+            jump.pen.move_to(pos, &mut jump.out);
+            jump.pen.write(row[x], &mut jump.out);
+
+            let mut fill = plan;
+            for &chr in &row[fill.pen.cursor.x..=x] {
+                fill.pen.write(chr, &mut fill.out);
+            }
+
+            plan = jump.min(fill);
+        }
+
+        plan
+    }
+
+    /// Bytes needed to update the screen by stepping to each changed cell
+    /// with `ESC A/B/C/D`, used to report how much was saved.
+    fn naive_cost(&self) -> usize {
+        let mut pen = self.pen;
+        let mut cost = 0;
+
+        for (i, (chr, prev)) in self.chars.iter().zip(&self.previous).enumerate() {
+            if chr == prev {
+                continue;
+            }
+
+            let pos = Self::from_index(i).unwrap();
+            cost += 2 * (pen.cursor.x.abs_diff(pos.x) + pen.cursor.y.abs_diff(pos.y));
+            pen.cursor = pos;
+
+            let mut out = Vec::new();
+            pen.write(*chr, &mut out);
+            cost += out.len();
+        }
+
+        cost
+    }
+
+    fn row(chars: &[Char; Self::SIZE], y: usize) -> &[Char] {
+        &chars[y * Self::WIDTH..][..Self::WIDTH]
+    }
+}
+
+impl Pen {
+    fn move_to(&mut self, pos: Vector2<usize>, out: &mut Vec<u8>) {
+        if self.cursor != pos {
+            out.extend(cursor_path(self.cursor, pos));
+            self.cursor = pos;
+        }
+    }
+
+    fn write(&mut self, chr: Char, out: &mut Vec<u8>) {
+        if chr.inverted != self.inverted {
+            self.inverted ^= true;
+            out.extend_from_slice([b"\x1Bq", b"\x1Bp"][self.inverted as usize]);
+        }
+
+        out.push(chr.char);
+
+        let idx = Screen::index(self.cursor).unwrap();
+        if idx + 1 < Screen::SIZE {
+            self.cursor = Screen::from_index(idx + 1).unwrap();
+        }
+    }
+}
+
+impl Plan {
+    fn new(pen: Pen) -> Self {
+        Self {
+            out: Vec::new(),
+            pen,
+        }
+    }
+
+    fn min(self, other: Self) -> Self {
+        if other.out.len() < self.out.len() {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+async fn send<T: AsyncWrite + Unpin>(writer: &mut T, out: &[u8]) -> Result<()> {
+    if !out.is_empty() {
+        trace!("Sent {}", out.escape_ascii());
+    }
+
+    writer.write_all(out).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Moves the contents of rows `top..=bottom` up by `lines`, or down if
+/// negative, filling the exposed rows with blanks.
+pub(super) fn shift(chars: &mut [Char; Screen::SIZE], top: usize, bottom: usize, lines: isize) {
+    let region = &mut chars[top * Screen::WIDTH..(bottom + 1) * Screen::WIDTH];
+    let n = (lines.unsigned_abs() * Screen::WIDTH).min(region.len());
+
+    if lines > 0 {
+        region.copy_within(n.., 0);
+        let len = region.len();
+        region[len - n..].fill(Char::default());
+    } else {
+        region.copy_within(..region.len() - n, n);
+        region[..n].fill(Char::default());
+    }
+}
+
+/// Finds the shortest byte sequence that moves the cursor from `from` to `to`,
+/// trying relative steps, direct addressing (`ESC Y`) and starting over from
+/// the line start (CR) or the home position.
+fn cursor_path(from: Vector2<usize>, to: Vector2<usize>) -> Vec<u8> {
+    let mut best = relative_path(from, to, Vec::new());
+
+    let candidates = [
+        vec![0x1B, b'Y', to.y as u8 + 0x20, to.x as u8 + 0x20],
+        relative_path(Vector2::new(0, from.y), to, vec![b'\r']),
+        relative_path(Vector2::zeros(), to, vec![0x0B]),
+    ];
+
+    for path in candidates {
+        if path.len() < best.len() {
+            best = path;
+        }
+    }
+
+    best
+}
+
+fn relative_path(from: Vector2<usize>, to: Vector2<usize>, mut out: Vec<u8>) -> Vec<u8> {
+    let horizontal: &[u8] = if to.x > from.x { b"\x1BC" } else { b"\x1BD" };
+    for _ in 0..from.x.abs_diff(to.x) {
+        out.extend_from_slice(horizontal);
+    }
+
+    // Line feed moves down without returning to the start of the line
+    let vertical: &[u8] = if to.y > from.y { b"\n" } else { b"\x1BA" };
+    for _ in 0..from.y.abs_diff(to.y) {
+        out.extend_from_slice(vertical);
+    }
+
+    out
+}
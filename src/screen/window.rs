@@ -0,0 +1,102 @@
+use nalgebra::Vector2;
+
+use super::{
+    Char, Screen, charset,
+    layout::{Wrap, Wrapped},
+};
+
+/// A rectangular view onto a [`Screen`]. Positions are relative to the
+/// window's origin and anything outside of it is clipped.
+pub struct Window<'a> {
+    screen: &'a mut Screen,
+    origin: Vector2<usize>,
+    size: Vector2<usize>,
+}
+
+impl Screen {
+    pub fn window(&mut self, origin: Vector2<usize>, size: Vector2<usize>) -> Window<'_> {
+        let bounds = Vector2::new(Self::WIDTH, Self::HEIGHT);
+        let origin = origin.inf(&bounds);
+        let size = size.inf(&(bounds - origin));
+
+        Window {
+            screen: self,
+            origin,
+            size,
+        }
+    }
+}
+
+impl Window<'_> {
+    pub fn origin(&self) -> Vector2<usize> {
+        self.origin
+    }
+
+    pub fn size(&self) -> Vector2<usize> {
+        self.size
+    }
+
+    /// Creates a window inside of this one, clipped to its bounds.
+    pub fn window(&mut self, origin: Vector2<usize>, size: Vector2<usize>) -> Window<'_> {
+        let origin = origin.inf(&self.size);
+        let size = size.inf(&(self.size - origin));
+
+        Window {
+            screen: self.screen,
+            origin: self.origin + origin,
+            size,
+        }
+    }
+
+    pub fn put(&mut self, pos: Vector2<usize>, chr: Char) {
+        if pos.x < self.size.x && pos.y < self.size.y {
+            self.screen.put(self.origin + pos, chr);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.rect(Vector2::zeros(), self.size, Char::default());
+    }
+
+    pub fn write_string(&mut self, pos: Vector2<usize>, str: &[u8]) {
+        self.write_string_inverted(pos, str, false);
+    }
+
+    /// Writes Unicode text, converted with [`charset::encode_str`].
+    pub fn write_str(&mut self, pos: Vector2<usize>, str: &str) {
+        self.write_string(pos, &charset::encode_str(str));
+    }
+
+    pub fn write_string_inverted(&mut self, pos: Vector2<usize>, str: &[u8], invert: bool) {
+        for (i, chr) in str.iter().enumerate() {
+            let mut chr = Char::from(*chr);
+            invert.then(|| chr = chr.invert());
+
+            self.put(pos + Vector2::x() * i, chr);
+        }
+    }
+
+    /// Wraps text to the width of the window from `pos`, stopping at the
+    /// bottom. Long words are split and line breaks are kept.
+    pub fn write_string_wrapped(&mut self, pos: Vector2<usize>, str: &[u8]) -> Wrapped {
+        let size = self.size.zip_map(&pos, |a, b| a.saturating_sub(b));
+        self.write_wrapped(pos, str, Wrap::new(size.x).height(size.y))
+    }
+
+    pub fn write_wrapped(&mut self, pos: Vector2<usize>, str: &[u8], wrap: Wrap) -> Wrapped {
+        let wrapped = wrap.wrap(str);
+        for (y, line) in wrapped.lines.iter().enumerate() {
+            self.write_string(pos + Vector2::y() * y, line);
+        }
+
+        wrapped
+    }
+
+    pub fn rect(&mut self, pos: Vector2<usize>, size: Vector2<usize>, chr: Char) {
+        for y in 0..size.y {
+            for x in 0..size.x {
+                self.put(pos + Vector2::new(x, y), chr);
+            }
+        }
+    }
+}
@@ -1,69 +1,180 @@
 use std::{
-    cmp::Ordering,
-    collections::BinaryHeap,
+    any::Any,
     mem,
     ops::{Deref, DerefMut},
+    sync::Arc,
     time::Duration,
 };
 
 use anyhow::Result;
 use tokio::{
-    io::{AsyncWriteExt, BufWriter, WriteHalf},
+    io::{self, AsyncReadExt, AsyncWriteExt, BufWriter, ReadHalf, WriteHalf},
     time::Instant,
 };
-use tokio_serial::SerialStream;
 
-use crate::screen::Screen;
+use tracing::trace;
 
-type SerialWriter = BufWriter<WriteHalf<SerialStream>>;
+use crate::{
+    key::KeyEvent,
+    keymap::Keymap,
+    screen::Screen,
+    timer::{Timer, TimerEvent, Timers},
+    transport::Transport,
+};
 
 pub struct State {
     screen: Screen,
-    writer: SerialWriter,
+    reader: ReadHalf<Box<dyn Transport>>,
+    writer: BufWriter<WriteHalf<Box<dyn Transport>>>,
 
-    pub(super) timeouts: BinaryHeap<Timeout>,
+    timers: Timers,
+    /// Module that timers scheduled now belong to, see [`State::set_owner`].
+    owner: u32,
     exit: bool,
-}
-
-pub(super) struct Timeout {
-    pub time: Instant,
-    pub kind: u32,
+    /// Set while a module in the background is running, so drawing only
+    /// updates its own copy of the screen.
+    hidden: bool,
+    /// Set when reading or writing fails, until [`State::reconnect`].
+    disconnected: bool,
+    keymap: Keymap,
 }
 
 impl State {
-    pub async fn new(mut writer: SerialWriter) -> Result<Self> {
-        // Reset screen, disable scroll, hide cursor
-        writer.write_all(b"\x0C\x1Bq\x1BV\x1BQ").await?;
-
-        Ok(Self {
+    pub async fn new(transport: Box<dyn Transport>) -> Result<Self> {
+        let (reader, writer) = io::split(transport);
+        let mut state = Self {
             screen: Screen::new(),
-            writer,
+            reader,
+            writer: BufWriter::new(writer),
 
-            timeouts: BinaryHeap::new(),
+            timers: Timers::default(),
+            owner: 0,
             exit: false,
-        })
+            hidden: false,
+            disconnected: false,
+            keymap: Keymap::default(),
+        };
+
+        state.reset().await?;
+        Ok(state)
+    }
+
+    async fn reset(&mut self) -> Result<()> {
+        // Reset screen, disable scroll, hide cursor
+        self.writer.write_all(b"\x0C\x1Bq\x1BV\x1BQ").await?;
+        Ok(())
+    }
+
+    /// Switches to a new transport after the old one failed, and sends the
+    /// whole screen again. Everything else, like timers, carries on.
+    pub async fn reconnect(&mut self, transport: Box<dyn Transport>) -> Result<()> {
+        let (reader, writer) = io::split(transport);
+        self.reader = reader;
+        self.writer = BufWriter::new(writer);
+        self.disconnected = false;
+
+        self.reset().await?;
+        self.redraw().await
+    }
+
+    /// Whether the transport has failed. Errors returned while this is set
+    /// came from the connection rather than the module that got them.
+    pub fn disconnected(&self) -> bool {
+        self.disconnected
+    }
+
+    pub async fn read_key(&mut self) -> Result<KeyEvent> {
+        let byte = self.reader.read_u8().await;
+        self.disconnected |= byte.is_err();
+
+        let byte = byte?;
+        trace!("Received {byte:#04X}");
+        Ok(KeyEvent::decode(byte))
     }
 
     pub async fn draw(&mut self) -> Result<()> {
-        self.screen.draw(&mut self.writer).await
+        if self.hidden {
+            return Ok(());
+        }
+
+        let result = self.screen.draw(&mut self.writer).await;
+        self.disconnected |= result.is_err();
+        result
     }
 
     pub async fn redraw(&mut self) -> Result<()> {
-        self.screen.redraw(&mut self.writer).await
+        if self.hidden {
+            return Ok(());
+        }
+
+        let result = self.screen.redraw(&mut self.writer).await;
+        self.disconnected |= result.is_err();
+        result
+    }
+
+    /// Calls back after `delay` with `payload`, which can be read back with
+    /// [`TimerEvent::payload`].
+    pub fn schedule<T: Any + Send + Sync>(&mut self, delay: Duration, payload: T) -> Timer {
+        self.timers
+            .schedule(self.owner, delay, None, Arc::new(payload))
+    }
+
+    /// Like [`State::schedule`], but calls back every `interval` until
+    /// cancelled.
+    pub fn repeat<T: Any + Send + Sync>(&mut self, interval: Duration, payload: T) -> Timer {
+        self.timers
+            .schedule(self.owner, interval, Some(interval), Arc::new(payload))
+    }
+
+    pub fn cancel(&mut self, timer: Timer) {
+        self.timers.cancel(timer);
     }
 
-    pub fn schedule(&mut self, duration: Duration, kind: u32) {
-        self.timeouts.push(Timeout {
-            time: Instant::now() + duration,
-            kind,
-        });
+    /// Cancels every timer of the current owner, which happens by itself
+    /// when a module exits.
+    pub fn cancel_all(&mut self) {
+        self.timers.cancel_owner(self.owner);
     }
 
-    pub fn unschedule(&mut self, kind: Option<u32>) {
-        self.timeouts.retain(|x| match kind {
-            Some(kind) => x.kind != kind,
-            None => false,
-        });
+    /// When the next timer is due.
+    pub fn next_timeout(&self) -> Option<Instant> {
+        self.timers.next()
+    }
+
+    /// Removes the timer that's due next, making its owner current so the
+    /// callback can be routed to it.
+    pub fn pop_timeout(&mut self) -> Option<TimerEvent> {
+        let (owner, event) = self.timers.pop()?;
+        self.owner = owner;
+        Some(event)
+    }
+
+    /// Which module is running, for telling timers apart. Zero is the menu.
+    pub fn owner(&self) -> u32 {
+        self.owner
+    }
+
+    pub(crate) fn set_owner(&mut self, owner: u32) {
+        self.owner = owner;
+    }
+
+    /// Whether a module in the background is running. It should avoid
+    /// dialogs, which would wait for keys meant for another module.
+    pub fn hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub(crate) fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    /// Global shortcuts, which dialogs check for redrawing too.
+    pub fn keymap(&self) -> &Keymap {
+        &self.keymap
+    }
+
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
     }
 
     pub fn exit(&mut self) {
@@ -88,23 +199,3 @@ impl DerefMut for State {
         &mut self.screen
     }
 }
-
-impl PartialEq for Timeout {
-    fn eq(&self, other: &Self) -> bool {
-        self.time == other.time
-    }
-}
-
-impl PartialOrd for Timeout {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for Timeout {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.time.cmp(&other.time)
-    }
-}
-
-impl Eq for Timeout {}
@@ -0,0 +1,119 @@
+//! Timers scheduled by modules. Each belongs to the module that scheduled it,
+//! so it's only delivered to that module and goes away when it exits.
+
+use std::{any::Any, cmp::Ordering, collections::BinaryHeap, sync::Arc, time::Duration};
+
+use tokio::time::Instant;
+
+/// Handle to a scheduled timer, for cancelling it or telling it apart from
+/// others when it fires.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Timer(u64);
+
+/// A timer that went off, handed to [`Module::callback`].
+///
+/// [`Module::callback`]: crate::modules::Module::callback
+pub struct TimerEvent {
+    pub timer: Timer,
+    payload: Arc<dyn Any + Send + Sync>,
+}
+
+/// Pending timers, earliest first.
+#[derive(Default)]
+pub(crate) struct Timers {
+    heap: BinaryHeap<Entry>,
+    next_id: u64,
+}
+
+struct Entry {
+    time: Instant,
+    timer: Timer,
+    owner: u32,
+    /// Set for timers that repeat.
+    interval: Option<Duration>,
+    payload: Arc<dyn Any + Send + Sync>,
+}
+
+impl TimerEvent {
+    /// The value passed when scheduling the timer, if it's a `T`.
+    pub fn payload<T: Any>(&self) -> Option<&T> {
+        self.payload.downcast_ref()
+    }
+}
+
+impl Timers {
+    pub fn schedule(
+        &mut self,
+        owner: u32,
+        delay: Duration,
+        interval: Option<Duration>,
+        payload: Arc<dyn Any + Send + Sync>,
+    ) -> Timer {
+        let timer = Timer(self.next_id);
+        self.next_id += 1;
+
+        self.heap.push(Entry {
+            time: Instant::now() + delay,
+            timer,
+            owner,
+            interval,
+            payload,
+        });
+        timer
+    }
+
+    pub fn cancel(&mut self, timer: Timer) {
+        self.heap.retain(|x| x.timer != timer);
+    }
+
+    pub fn cancel_owner(&mut self, owner: u32) {
+        self.heap.retain(|x| x.owner != owner);
+    }
+
+    pub fn next(&self) -> Option<Instant> {
+        self.heap.peek().map(|x| x.time)
+    }
+
+    /// Removes the timer that's due next, putting it back for its next run if
+    /// it repeats. Returns it along with its owner.
+    pub fn pop(&mut self) -> Option<(u32, TimerEvent)> {
+        let entry = self.heap.pop()?;
+        let event = TimerEvent {
+            timer: entry.timer,
+            payload: entry.payload.clone(),
+        };
+
+        let owner = entry.owner;
+        if let Some(interval) = entry.interval {
+            // From when it was due so it doesn't drift, unless it's fallen
+            // behind, like while a dialog was open
+            self.heap.push(Entry {
+                time: (entry.time + interval).max(Instant::now()),
+                ..entry
+            });
+        }
+
+        Some((owner, event))
+    }
+}
+
+impl PartialEq for Entry {
+    fn eq(&self, other: &Self) -> bool {
+        self.time == other.time
+    }
+}
+
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Entry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, so the heap pops the earliest timer first
+        other.time.cmp(&self.time)
+    }
+}
+
+impl Eq for Entry {}
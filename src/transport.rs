@@ -0,0 +1,98 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use anyhow::{Context as _, Result};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::{TcpStream, UnixStream},
+};
+use tokio_serial::{SerialPort, SerialPortBuilderExt, SerialStream};
+use tracing::info;
+
+use crate::config::SerialConfig;
+
+/// A byte stream connected to a Model 100. Anything readable and writable
+/// works, so a serial port, network socket or an in-memory duplex can all
+/// drive the same modules.
+pub trait Transport: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Transport for T {}
+
+/// The controlling side of a pseudo terminal. The other end is kept open so
+/// reads don't fail before something attaches to it.
+pub struct Pty {
+    master: SerialStream,
+    slave: SerialStream,
+}
+
+/// Opens the transport described by `serial.port`:
+/// - `tcp://host:port` connects to a network serial server (ser2net, WiFi modems)
+/// - `unix:/path` connects to a Unix socket
+/// - `pty` creates a new pseudo terminal and prints the path to attach to
+/// - anything else is opened as a serial device with the configured settings
+pub async fn open(serial: &SerialConfig) -> Result<Box<dyn Transport>> {
+    let port = serial.port.as_str();
+
+    Ok(if let Some(addr) = port.strip_prefix("tcp://") {
+        let stream = TcpStream::connect(addr)
+            .await
+            .with_context(|| format!("Failed to connect to `{addr}`"))?;
+        stream.set_nodelay(true)?;
+        Box::new(stream)
+    } else if let Some(path) = port.strip_prefix("unix:") {
+        let stream = UnixStream::connect(path)
+            .await
+            .with_context(|| format!("Failed to connect to `{path}`"))?;
+        Box::new(stream)
+    } else if port == "pty" {
+        let pty = Pty::new()?;
+        if let Some(name) = pty.slave.name() {
+            info!("Attach to {name}");
+        }
+        Box::new(pty)
+    } else {
+        let stream = serial
+            .builder()?
+            .open_native_async()
+            .with_context(|| format!("Failed to open `{port}`"))?;
+        Box::new(stream)
+    })
+}
+
+impl Pty {
+    pub fn new() -> Result<Self> {
+        let (master, slave) = SerialStream::pair()?;
+        Ok(Self { master, slave })
+    }
+}
+
+impl AsyncRead for Pty {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.master).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Pty {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.master).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.master).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.master).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,192 @@
+//! Modal dialogs drawn over the current screen. Each one reads keys until
+//! it's dismissed and then puts back what was underneath. Timers don't fire
+//! while waiting for an answer.
+
+use anyhow::Result;
+use nalgebra::Vector2;
+
+use crate::{
+    key::{KeyCode, KeyEvent},
+    keymap::Action,
+    screen::{Char, FrameStyle, Screen, Window, Wrap, glyph},
+    state::State,
+    widgets::editor::{Edit, LineEditor},
+};
+
+/// Widest message that fits with the border and padding.
+const MAX_WIDTH: usize = Screen::WIDTH - 4;
+
+/// Asks a yes or no question. Y and N answer directly, the arrow keys move
+/// between the buttons and ESC answers no.
+pub async fn confirm(state: &mut State, message: &[u8]) -> Result<bool> {
+    let saved = *state.chars();
+    let mut yes = true;
+
+    let answer = loop {
+        let mut window = open(state, message, 1, 10);
+        let y = window.size().y - 1;
+        let x = window.size().x / 2;
+        window.write_string_inverted(Vector2::new(x - 6, y), b" Yes ", yes);
+        window.write_string_inverted(Vector2::new(x + 2, y), b" No ", !yes);
+        state.draw().await?;
+
+        let key = read_key(state).await?;
+        match (key.code, key.char()) {
+            (_, Some(b'y' | b'Y')) => break true,
+            (KeyCode::Esc, _) | (_, Some(b'n' | b'N')) => break false,
+            (KeyCode::Enter, _) => break yes,
+            (KeyCode::Left | KeyCode::Right, _) => yes ^= true,
+            _ => {}
+        }
+    };
+
+    close(state, &saved).await?;
+    Ok(answer)
+}
+
+/// Shows a message until ENTER, ESC or space is pressed.
+pub async fn alert(state: &mut State, message: &[u8]) -> Result<()> {
+    let saved = *state.chars();
+
+    let mut window = open(state, message, 1, 0);
+    let pos = Vector2::new(window.size().x / 2 - 2, window.size().y - 1);
+    window.write_string_inverted(pos, b" OK ", true);
+    state.draw().await?;
+
+    while !matches!(
+        read_key(state).await?.code,
+        KeyCode::Enter | KeyCode::Esc | KeyCode::Char(b' ')
+    ) {}
+
+    close(state, &saved).await
+}
+
+/// Asks for a line of text, starting with `initial`. Returns `None` if ESC
+/// was pressed.
+pub async fn prompt(state: &mut State, message: &[u8], initial: &[u8]) -> Result<Option<Vec<u8>>> {
+    let saved = *state.chars();
+    let mut editor = LineEditor::new(b"");
+    editor.set_text(initial.to_vec());
+
+    let answer = loop {
+        let mut window = open(state, message, 1, MAX_WIDTH);
+        let y = window.size().y - 1;
+        let mut input = window.window(Vector2::new(1, y), Vector2::new(MAX_WIDTH, 1));
+        editor.draw(&mut input);
+        state.draw().await?;
+
+        match read_key(state).await? {
+            key if key.code == KeyCode::Esc => break None,
+            key => {
+                if let Edit::Submit(text) = editor.on_key(key) {
+                    break Some(text);
+                }
+            }
+        }
+    };
+
+    close(state, &saved).await?;
+    Ok(answer)
+}
+
+/// A progress bar shown while work continues in the background. Unlike the
+/// other dialogs it doesn't read keys, the owner updates it and closes it.
+pub struct Progress {
+    saved: Box<[Char; Screen::SIZE]>,
+    message: Vec<u8>,
+    step: usize,
+}
+
+impl Progress {
+    pub async fn show(state: &mut State, message: &[u8]) -> Result<Self> {
+        let mut progress = Self {
+            saved: Box::new(*state.chars()),
+            message: message.to_vec(),
+            step: 0,
+        };
+
+        progress.set(state, Some(0.0)).await?;
+        Ok(progress)
+    }
+
+    pub fn set_message(&mut self, message: &[u8]) {
+        self.message = message.to_vec();
+    }
+
+    /// Fills the bar to `fraction` of its width, or when `None`, moves a
+    /// block back and forth to show that something is still happening.
+    pub async fn set(&mut self, state: &mut State, fraction: Option<f32>) -> Result<()> {
+        state.restore(&self.saved);
+        let mut window = open(state, &self.message, 1, MAX_WIDTH);
+        let y = window.size().y - 1;
+        let mut bar = window.window(Vector2::new(1, y), Vector2::new(MAX_WIDTH, 1));
+
+        let width = bar.size().x;
+        bar.rect(Vector2::zeros(), bar.size(), glyph::HORIZONTAL.into());
+        match fraction {
+            Some(fraction) => {
+                let filled = (fraction.clamp(0.0, 1.0) * width as f32).round() as usize;
+                let size = Vector2::new(filled, 1);
+                bar.rect(Vector2::zeros(), size, glyph::FULL_BLOCK.into());
+            }
+            None => {
+                const BLOCK: usize = 4;
+                let span = width - BLOCK;
+                let x = match (self.step / span) % 2 {
+                    0 => self.step % span,
+                    _ => span - self.step % span,
+                };
+                self.step += 1;
+
+                let pos = Vector2::x() * x;
+                bar.rect(pos, Vector2::new(BLOCK, 1), glyph::FULL_BLOCK.into());
+            }
+        }
+
+        state.draw().await
+    }
+
+    pub async fn close(&self, state: &mut State) -> Result<()> {
+        close(state, &self.saved).await
+    }
+}
+
+/// Draws an empty box centered on the screen fitting `message` and `rows`
+/// more lines below it, at least `width` wide. Returns the area inside.
+fn open<'a>(state: &'a mut State, message: &[u8], rows: usize, width: usize) -> Window<'a> {
+    let height = Screen::HEIGHT - 2 - rows;
+    let wrapped = Wrap::new(MAX_WIDTH)
+        .height(height)
+        .ellipsis(true)
+        .wrap(message);
+
+    let longest = wrapped.lines.iter().map(Vec::len).max().unwrap_or(0);
+    let inner = Vector2::new(longest.max(width).max(8), wrapped.line_count() + rows);
+    let size = inner + Vector2::new(4, 2);
+    let pos = Vector2::new(Screen::WIDTH - size.x, Screen::HEIGHT - size.y) / 2;
+
+    let mut window = state.frame(pos, size, FrameStyle::Single);
+    window.clear();
+    for (y, line) in wrapped.lines.iter().enumerate() {
+        window.write_string(Vector2::new(1, y), line);
+    }
+
+    window
+}
+
+async fn close(state: &mut State, saved: &[Char; Screen::SIZE]) -> Result<()> {
+    state.restore(saved);
+    state.draw().await
+}
+
+/// Reads a key, handling the redraw shortcut like the menu does so the screen
+/// can still be redrawn while a dialog is open.
+async fn read_key(state: &mut State) -> Result<KeyEvent> {
+    loop {
+        let key = state.read_key().await?;
+        match state.keymap().action(key) {
+            Some(Action::Redraw) => state.redraw().await?,
+            _ => return Ok(key),
+        }
+    }
+}
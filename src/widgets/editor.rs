@@ -0,0 +1,213 @@
+use std::mem;
+
+use nalgebra::Vector2;
+
+use crate::{
+    key::{KeyCode, KeyEvent, KeyModifiers},
+    screen::{Char, Window},
+};
+
+/// Editable line of text with cursor movement and history. In multi-line
+/// mode the text wraps onto as many rows as the window has instead of
+/// scrolling sideways.
+///
+/// CTRL+left/right (`^Q`/`^R`) jump by word and SHIFT+left/right (`^A`/`^F`)
+/// go to the start or end.
+pub struct LineEditor {
+    prompt: Vec<u8>,
+    buffer: Vec<u8>,
+    cursor: usize,
+    /// First visible character when scrolling sideways.
+    scroll: usize,
+    multiline: bool,
+    /// From the last draw, used to move between rows.
+    width: usize,
+
+    history: Vec<Vec<u8>>,
+    /// Entry being shown from the history, and the unsent input to return to.
+    recall: Option<(usize, Vec<u8>)>,
+}
+
+pub enum Edit {
+    /// The key isn't used by the editor.
+    Ignored,
+    Changed,
+    /// Enter was pressed, the input is cleared and added to the history.
+    Submit(Vec<u8>),
+}
+
+impl LineEditor {
+    pub fn new(prompt: &[u8]) -> Self {
+        Self {
+            prompt: prompt.to_vec(),
+            buffer: Vec::new(),
+            cursor: 0,
+            scroll: 0,
+            multiline: false,
+            width: 0,
+
+            history: Vec::new(),
+            recall: None,
+        }
+    }
+
+    pub fn multiline(mut self, multiline: bool) -> Self {
+        self.multiline = multiline;
+        self
+    }
+
+    pub fn text(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    pub fn set_text(&mut self, text: Vec<u8>) {
+        self.cursor = text.len();
+        self.buffer = text;
+    }
+
+    /// Rows needed to show the whole input in multi-line mode.
+    pub fn rows(&self, width: usize) -> usize {
+        match self.multiline {
+            true => (self.prompt.len() + self.buffer.len()) / width.max(1) + 1,
+            false => 1,
+        }
+    }
+
+    pub fn on_key(&mut self, key: KeyEvent) -> Edit {
+        if let Some(chr) = key.char() {
+            self.buffer.insert(self.cursor, chr);
+            self.cursor += 1;
+            return Edit::Changed;
+        }
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Enter, _) => {
+                let input = mem::take(&mut self.buffer);
+                self.cursor = 0;
+                self.scroll = 0;
+                self.recall = None;
+
+                if !input.is_empty() && self.history.last() != Some(&input) {
+                    self.history.push(input.clone());
+                }
+                return Edit::Submit(input);
+            }
+            (KeyCode::Backspace, _) if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.buffer.remove(self.cursor);
+            }
+            (KeyCode::Delete, _) if self.cursor < self.buffer.len() => {
+                self.buffer.remove(self.cursor);
+            }
+            (KeyCode::Left, KeyModifiers::NONE) => self.cursor = self.cursor.saturating_sub(1),
+            (KeyCode::Right, KeyModifiers::NONE) => {
+                self.cursor = (self.cursor + 1).min(self.buffer.len())
+            }
+            (KeyCode::Left, KeyModifiers::CTRL) => self.cursor = self.word_start(),
+            (KeyCode::Right, KeyModifiers::CTRL) => self.cursor = self.word_end(),
+            (KeyCode::Left, KeyModifiers::SHIFT) => self.cursor = 0,
+            (KeyCode::Right, KeyModifiers::SHIFT) => self.cursor = self.buffer.len(),
+            (KeyCode::Up, KeyModifiers::NONE) => return self.vertical(false),
+            (KeyCode::Down, KeyModifiers::NONE) => return self.vertical(true),
+            (KeyCode::Backspace | KeyCode::Delete, _) => {}
+            _ => return Edit::Ignored,
+        }
+
+        Edit::Changed
+    }
+
+    pub fn draw(&mut self, window: &mut Window) {
+        let size = window.size();
+        self.width = size.x;
+        window.clear();
+        if size.x == 0 || size.y == 0 {
+            return;
+        }
+
+        let text = [self.prompt.as_slice(), &self.buffer, b" "].concat();
+        let cursor = self.prompt.len() + self.cursor;
+
+        if self.multiline {
+            // Keep the row with the cursor on screen
+            let row = cursor / size.x;
+            let first = (row + 1).saturating_sub(size.y);
+            for (y, line) in text.chunks(size.x).skip(first).take(size.y).enumerate() {
+                window.write_string(Vector2::new(0, y), line);
+            }
+
+            let pos = Vector2::new(cursor % size.x, row - first);
+            window.put(pos, Char::from(text[cursor]).invert());
+            return;
+        }
+
+        self.scroll = self
+            .scroll
+            .min(cursor)
+            .max((cursor + 1).saturating_sub(size.x));
+        let visible = &text[self.scroll..];
+        window.write_string(Vector2::zeros(), &visible[..visible.len().min(size.x)]);
+        window.put(
+            Vector2::x() * (cursor - self.scroll),
+            Char::from(text[cursor]).invert(),
+        );
+    }
+
+    /// Moves through the history, or between rows when editing multiple lines.
+    fn vertical(&mut self, down: bool) -> Edit {
+        if self.multiline && self.width > 0 {
+            let target = match down {
+                true => Some(self.cursor + self.width).filter(|&x| x <= self.buffer.len()),
+                false => self.cursor.checked_sub(self.width),
+            };
+            if let Some(target) = target {
+                self.cursor = target;
+                return Edit::Changed;
+            }
+        }
+
+        if self.recall.is_none() && self.history.is_empty() {
+            return Edit::Ignored;
+        }
+
+        let (index, draft) = match self.recall.take() {
+            Some((index, draft)) => (Some(index), draft),
+            None => (None, self.buffer.clone()),
+        };
+
+        let next = match (index, down) {
+            (None, false) => Some(self.history.len() - 1),
+            (None, true) => None,
+            (Some(0), false) => Some(0),
+            (Some(i), false) => Some(i - 1),
+            (Some(i), true) => (i + 1 < self.history.len()).then_some(i + 1),
+        };
+
+        match next {
+            Some(index) => {
+                self.set_text(self.history[index].clone());
+                self.recall = Some((index, draft));
+            }
+            None => self.set_text(draft),
+        }
+
+        Edit::Changed
+    }
+
+    fn word_start(&self) -> usize {
+        let before = &self.buffer[..self.cursor];
+        let end = before.iter().rposition(|x| *x != b' ').map_or(0, |x| x + 1);
+        before[..end]
+            .iter()
+            .rposition(|x| *x == b' ')
+            .map_or(0, |x| x + 1)
+    }
+
+    fn word_end(&self) -> usize {
+        let after = &self.buffer[self.cursor..];
+        let start = after.iter().position(|x| *x != b' ').unwrap_or(after.len());
+        let end =
+            (after[start..].iter().position(|x| *x == b' ')).map_or(after.len(), |x| start + x);
+        let next = (after[end..].iter().position(|x| *x != b' ')).map_or(after.len(), |x| end + x);
+        self.cursor + next
+    }
+}
@@ -0,0 +1,187 @@
+use nalgebra::Vector2;
+
+use crate::{
+    key::{KeyCode, KeyEvent},
+    screen::{Window, glyph},
+};
+
+/// Vertical list of centered items. The arrow keys move the selection,
+/// wrapping around at either end, and typing the first letter of an item
+/// jumps to it. Disabled items are shown in parentheses and can't be selected.
+pub struct List {
+    items: Vec<Item>,
+    selected: Option<usize>,
+    scroll: usize,
+}
+
+pub struct Item {
+    label: Vec<u8>,
+    enabled: bool,
+    hotkey: Option<u8>,
+}
+
+pub enum Select {
+    /// The key isn't used by the list.
+    Ignored,
+    Moved,
+    /// Enter was pressed, or the hotkey of the only item starting with it.
+    Chosen(usize),
+}
+
+impl Item {
+    pub fn new(label: &[u8]) -> Self {
+        Self {
+            label: label.to_vec(),
+            enabled: true,
+            hotkey: None,
+        }
+    }
+
+    /// Uses another key than the first letter of the label.
+    pub fn hotkey(mut self, hotkey: Option<u8>) -> Self {
+        self.hotkey = hotkey;
+        self
+    }
+
+    pub fn enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    pub fn label(&self) -> &[u8] {
+        &self.label
+    }
+
+    fn key(&self) -> Option<u8> {
+        let key = self.hotkey.or(self.label.first().copied());
+        key.map(|x| x.to_ascii_lowercase())
+    }
+}
+
+impl List {
+    pub fn new(items: Vec<Item>) -> Self {
+        let selected = items.iter().position(|x| x.enabled);
+        Self {
+            items,
+            selected,
+            scroll: 0,
+        }
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// The selected item, or `None` if every item is disabled.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if self.items.get(index).is_some_and(|x| x.enabled) {
+            self.selected = Some(index);
+        }
+    }
+
+    pub fn on_key(&mut self, key: KeyEvent) -> Select {
+        let Some(selected) = self.selected else {
+            return Select::Ignored;
+        };
+
+        match key.code {
+            KeyCode::Up => self.selected = self.next(selected, false),
+            KeyCode::Down => self.selected = self.next(selected, true),
+            KeyCode::Enter => return Select::Chosen(selected),
+            _ => {
+                let Some(key) = key.char().map(|x| x.to_ascii_lowercase()) else {
+                    return Select::Ignored;
+                };
+                let matches = |x: &Item| x.enabled && x.key() == Some(key);
+                if !key.is_ascii_alphanumeric() || !self.items.iter().any(matches) {
+                    return Select::Ignored;
+                }
+
+                // Repeated presses cycle through the items sharing a letter
+                let mut index = selected;
+                loop {
+                    index = (index + 1) % self.items.len();
+                    if matches(&self.items[index]) {
+                        break;
+                    }
+                }
+                self.selected = Some(index);
+
+                if self.items.iter().filter(|x| matches(x)).count() == 1 {
+                    return Select::Chosen(index);
+                }
+            }
+        }
+
+        Select::Moved
+    }
+
+    pub fn draw(&mut self, window: &mut Window) {
+        let size = window.size();
+        window.clear();
+        if size.x == 0 || size.y == 0 {
+            return;
+        }
+
+        let scrollable = self.items.len() > size.y;
+        let width = size.x - scrollable as usize;
+
+        if let Some(selected) = self.selected {
+            self.scroll = self
+                .scroll
+                .min(selected)
+                .max((selected + 1).saturating_sub(size.y));
+        }
+        self.scroll = self.scroll.min(self.items.len().saturating_sub(size.y));
+
+        for (y, (i, item)) in self
+            .items
+            .iter()
+            .enumerate()
+            .skip(self.scroll)
+            .take(size.y)
+            .enumerate()
+        {
+            let label = match item.enabled {
+                true => item.label.clone(),
+                false => [b"(", item.label.as_slice(), b")"].concat(),
+            };
+
+            let x = (width / 2).saturating_sub(label.len() / 2);
+            let selected = self.selected == Some(i);
+            if selected {
+                window.put(Vector2::new(x.saturating_sub(2), y), b'>'.into());
+                window.put(Vector2::new(x + label.len() + 1, y), b'<'.into());
+            }
+            window.write_string_inverted(Vector2::new(x, y), &label, selected);
+        }
+
+        if scrollable {
+            let max_scroll = self.items.len() - size.y;
+            let thumb = self.scroll * (size.y - 1) / max_scroll;
+            for y in 0..size.y {
+                let chr = if y == thumb {
+                    glyph::FULL_BLOCK
+                } else {
+                    glyph::VERTICAL
+                };
+                window.put(Vector2::new(size.x - 1, y), chr.into());
+            }
+        }
+    }
+
+    /// The next enabled item in a direction, wrapping around the ends.
+    fn next(&self, from: usize, down: bool) -> Option<usize> {
+        let len = self.items.len();
+        (1..=len)
+            .map(|x| match down {
+                true => (from + x) % len,
+                false => (from + len - x) % len,
+            })
+            .find(|&x| self.items[x].enabled)
+    }
+}
@@ -0,0 +1,6 @@
+//! Reusable interface components that draw into a [`Window`](crate::screen::Window).
+
+pub mod dialog;
+pub mod editor;
+pub mod list;
+pub mod viewer;
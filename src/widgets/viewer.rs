@@ -0,0 +1,140 @@
+use nalgebra::Vector2;
+
+use crate::{
+    key::{KeyCode, KeyEvent, KeyModifiers},
+    screen::{Window, Wrap, glyph},
+};
+
+/// Scrollable, word wrapped text. Scrolls with the arrow keys, pages with
+/// SHIFT+arrows and jumps to the top or bottom with CTRL+arrows. A scroll bar
+/// is shown in the rightmost column when the text doesn't fit.
+pub struct Viewer {
+    text: Vec<u8>,
+    scroll: usize,
+    /// Stay at the bottom as text is added.
+    follow: bool,
+
+    // From the last draw, used to limit scrolling
+    lines: usize,
+    height: usize,
+}
+
+impl Viewer {
+    pub fn new() -> Self {
+        Self {
+            text: Vec::new(),
+            scroll: 0,
+            follow: true,
+
+            lines: 0,
+            height: 0,
+        }
+    }
+
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Replaces the text, following the end of it as more is pushed.
+    pub fn set_text(&mut self, text: Vec<u8>) {
+        self.text = text;
+        self.scroll = 0;
+        self.follow = true;
+        self.lines = 0;
+    }
+
+    /// Adds text to the end, staying scrolled to the bottom if already there.
+    pub fn push(&mut self, text: &[u8]) {
+        self.text.extend_from_slice(text);
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text(Vec::new());
+    }
+
+    pub fn top(&mut self) {
+        self.scroll = 0;
+        self.follow = self.max_scroll() == 0;
+    }
+
+    pub fn bottom(&mut self) {
+        self.scroll = self.max_scroll();
+        self.follow = true;
+    }
+
+    pub fn scroll_by(&mut self, lines: isize) {
+        self.scroll = self
+            .scroll
+            .saturating_add_signed(lines)
+            .min(self.max_scroll());
+        self.follow = self.scroll == self.max_scroll();
+    }
+
+    /// Handles scrolling keys, returning false for any other key.
+    pub fn on_key(&mut self, key: KeyEvent) -> bool {
+        let page = self.height.max(2) as isize - 1;
+        match (key.code, key.modifiers) {
+            (KeyCode::Up, KeyModifiers::NONE) => self.scroll_by(-1),
+            (KeyCode::Down, KeyModifiers::NONE) => self.scroll_by(1),
+            (KeyCode::Up, KeyModifiers::SHIFT) => self.scroll_by(-page),
+            (KeyCode::Down, KeyModifiers::SHIFT) => self.scroll_by(page),
+            (KeyCode::Up, KeyModifiers::CTRL) => self.top(),
+            (KeyCode::Down, KeyModifiers::CTRL) => self.bottom(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    pub fn draw(&mut self, window: &mut Window) {
+        let size = window.size();
+        let mut wrapped = Wrap::new(size.x).wrap(&self.text);
+
+        // Make room for the scroll bar
+        let scrollable = wrapped.line_count() > size.y;
+        if scrollable {
+            wrapped = Wrap::new(size.x.saturating_sub(1)).wrap(&self.text);
+        }
+
+        self.lines = wrapped.line_count();
+        self.height = size.y;
+        self.scroll = match self.follow {
+            true => self.max_scroll(),
+            false => self.scroll.min(self.max_scroll()),
+        };
+
+        window.clear();
+        for (y, line) in wrapped
+            .lines
+            .iter()
+            .skip(self.scroll)
+            .take(size.y)
+            .enumerate()
+        {
+            window.write_string(Vector2::new(0, y), line);
+        }
+
+        if scrollable {
+            let x = size.x - 1;
+            let thumb = self.scroll * (size.y - 1) / self.max_scroll().max(1);
+            for y in 0..size.y {
+                let chr = if y == thumb {
+                    glyph::FULL_BLOCK
+                } else {
+                    glyph::VERTICAL
+                };
+                window.put(Vector2::new(x, y), chr.into());
+            }
+        }
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.lines.saturating_sub(self.height)
+    }
+}
+
+impl Default for Viewer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,69 @@
+use model_100_serial::screen::{Screen, charset};
+use nalgebra::Vector2;
+
+#[test]
+fn round_trip() {
+    for byte in 0x20..=0xFF {
+        if byte == 0x7F || charset::decode(byte) == char::REPLACEMENT_CHARACTER {
+            continue;
+        }
+
+        assert_eq!(charset::encode(charset::decode(byte)), byte, "{byte:#X}");
+    }
+}
+
+#[test]
+fn transliteration() {
+    assert_eq!(
+        charset::encode_str("“Café” — naïve…"),
+        b"\"Cafe\" - naive...".to_vec()
+    );
+    assert_eq!(charset::encode_str("Łódź"), b"Lodz".to_vec());
+    assert_eq!(charset::encode('╔'), 0xF0);
+    assert_eq!(charset::encode('😀'), b'?');
+}
+
+#[test]
+fn known_codes() {
+    let codes = [
+        (0xE9, '█'),
+        (0xF0, '┌'),
+        (0xF1, '─'),
+        (0xF2, '┐'),
+        (0xF4, '├'),
+        (0xF5, '│'),
+        (0xF6, '└'),
+        (0xF7, '┘'),
+        (0xF9, '┤'),
+    ];
+    for (byte, chr) in codes {
+        assert_eq!(charset::decode(byte), chr, "{byte:#X}");
+        assert_eq!(charset::encode(chr), byte, "{chr:?}");
+    }
+    assert_eq!(charset::decode(0x80), char::REPLACEMENT_CHARACTER);
+}
+
+#[test]
+fn no_control_characters() {
+    for chr in ('\0'..='\x7F').chain(['\u{85}', '\u{9B}']) {
+        let byte = charset::encode(chr);
+        assert!(byte >= 0x20 && byte != 0x7F, "{chr:?}");
+    }
+
+    let text = "a\tb\x1B[2Jc\x07\r\n\x7F";
+    assert_eq!(charset::encode_str(text), b"a b?[2Jc????".to_vec());
+    assert_eq!(charset::encode_text(text), b"a b?[2Jc?\n?".to_vec());
+}
+
+#[test]
+fn write_str() {
+    let mut screen = Screen::new();
+    screen.write_str(Vector2::new(0, 0), "Año 3×4");
+
+    let row = screen.chars()[..7]
+        .iter()
+        .map(|x| x.char())
+        .collect::<Vec<_>>();
+    assert_eq!(row, b"Ano 3x4");
+    assert_eq!(charset::decode_bytes(&row), "Ano 3x4");
+}
@@ -0,0 +1,113 @@
+#![allow(dead_code)]
+
+use std::{env, fs, path::PathBuf};
+
+use futures::FutureExt;
+use model_100_serial::{
+    config::ModuleConfig,
+    emulator::Emulator,
+    key::KeyEvent,
+    modules::{Module, menu::Menu, registry::Registry},
+    state::State,
+};
+use tokio::io::{self, AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+/// Drives a [`Menu`] over an in-memory transport and interprets everything it
+/// sends with an [`Emulator`].
+pub struct Harness {
+    pub state: State,
+    pub menu: Menu,
+    pub emulator: Emulator,
+    device: DuplexStream,
+}
+
+impl Harness {
+    pub async fn new() -> Self {
+        Self::with_menu(Menu::new(Registry::default(), modules()).unwrap()).await
+    }
+
+    pub async fn with_menu(menu: Menu) -> Self {
+        let (host, device) = io::duplex(1 << 16);
+        let mut harness = Self {
+            state: State::new(Box::new(host)).await.unwrap(),
+            menu,
+            emulator: Emulator::new(),
+            device,
+        };
+
+        harness.menu.init(&mut harness.state).await.unwrap();
+        harness.sync();
+        harness
+    }
+
+    pub async fn press(&mut self, keys: &[u8]) {
+        for &byte in keys {
+            let key = KeyEvent::decode(byte);
+            self.menu.on_key(&mut self.state, key).await.unwrap();
+            self.sync();
+        }
+    }
+
+    /// Runs the callback for the timer that's due next, without waiting.
+    pub async fn fire(&mut self) {
+        let event = self.state.pop_timeout().expect("no timers");
+        self.menu.callback(&mut self.state, event).await.unwrap();
+        self.sync();
+    }
+
+    /// Types keys on the Model 100 side, for code that reads them itself
+    /// rather than through the menu.
+    pub async fn send(&mut self, keys: &[u8]) {
+        self.device.write_all(keys).await.unwrap();
+    }
+
+    /// Feeds all bytes sent so far into the emulator.
+    pub fn sync(&mut self) {
+        let mut buf = [0; 1024];
+        while let Some(read) = self.device.read(&mut buf).now_or_never() {
+            let read = read.unwrap();
+            self.emulator.feed(&buf[..read]);
+        }
+    }
+
+    #[track_caller]
+    pub fn assert_snapshot(&self, name: &str) {
+        assert_snapshot(name, &self.emulator.snapshot());
+    }
+}
+
+/// Every registered module, with a fake API key so ChatGPT opens.
+pub fn modules() -> Vec<ModuleConfig> {
+    let mut modules: Vec<_> = Registry::default()
+        .iter()
+        .map(|x| ModuleConfig::new(x.kind))
+        .collect();
+    for module in &mut modules {
+        if module.kind == "chatgpt" {
+            module.settings.insert("api_key".into(), "test".into());
+        }
+    }
+    modules
+}
+
+/// Compares `actual` against `tests/snapshots/<name>.txt`. Run with
+/// `UPDATE_SNAPSHOTS=1` to write new snapshots instead.
+#[track_caller]
+pub fn assert_snapshot(name: &str, actual: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name)
+        .with_extension("txt");
+
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing snapshot `{}`", path.display()));
+    assert!(
+        expected == actual,
+        "snapshot `{name}` does not match\n--- expected\n{expected}--- actual\n{actual}"
+    );
+}
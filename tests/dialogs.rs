@@ -0,0 +1,89 @@
+mod common;
+
+use common::Harness;
+use futures::FutureExt;
+use model_100_serial::widgets::dialog::{self, Progress};
+
+#[tokio::test]
+async fn confirm() {
+    let mut harness = Harness::new().await;
+
+    // Still waiting for an answer after the first draw. Dropping it leaves
+    // the dialog on screen, so put the menu back by hand.
+    let saved = *harness.state.chars();
+    let pending = dialog::confirm(&mut harness.state, b"Cancel print job?").now_or_never();
+    assert!(pending.is_none());
+    harness.sync();
+    harness.assert_snapshot("confirm");
+    harness.state.restore(&saved);
+
+    harness.send(b"\x1C\r").await;
+    let answer = dialog::confirm(&mut harness.state, b"Cancel print job?").await;
+    assert!(!answer.unwrap());
+
+    harness.send(b"y").await;
+    let answer = dialog::confirm(&mut harness.state, b"Cancel print job?").await;
+    assert!(answer.unwrap());
+
+    // The menu is back as it was
+    harness.sync();
+    harness.assert_snapshot("menu");
+}
+
+#[tokio::test]
+async fn alert() {
+    let mut harness = Harness::new().await;
+
+    let message = b"The printer is out of paper. Load more and try again.";
+    let saved = *harness.state.chars();
+    let pending = dialog::alert(&mut harness.state, message).now_or_never();
+    assert!(pending.is_none());
+    harness.sync();
+    harness.assert_snapshot("alert");
+    harness.state.restore(&saved);
+
+    harness.send(b"\r").await;
+    dialog::alert(&mut harness.state, message).await.unwrap();
+    harness.sync();
+    harness.assert_snapshot("menu");
+}
+
+#[tokio::test]
+async fn prompt() {
+    let mut harness = Harness::new().await;
+
+    harness.send(b"\x08s!\r").await;
+    let answer = dialog::prompt(&mut harness.state, b"File name:", b"note")
+        .await
+        .unwrap();
+    assert_eq!(answer.as_deref(), Some(b"nots!".as_slice()));
+
+    harness.send(b"abc\x1B").await;
+    let answer = dialog::prompt(&mut harness.state, b"File name:", b"")
+        .await
+        .unwrap();
+    assert_eq!(answer, None);
+}
+
+#[tokio::test]
+async fn progress() {
+    let mut harness = Harness::new().await;
+
+    let mut progress = Progress::show(&mut harness.state, b"Uploading")
+        .await
+        .unwrap();
+    progress.set(&mut harness.state, Some(0.25)).await.unwrap();
+    harness.sync();
+    harness.assert_snapshot("progress");
+
+    progress.set_message(b"Printing");
+    for _ in 0..3 {
+        progress.set(&mut harness.state, None).await.unwrap();
+    }
+    harness.sync();
+    harness.assert_snapshot("progress_indeterminate");
+
+    progress.close(&mut harness.state).await.unwrap();
+    harness.sync();
+    harness.assert_snapshot("menu");
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::cell::RefCell;
+
+use common::Harness;
+use enigo::{Direction, InputResult, Key, Keyboard};
+use model_100_serial::{
+    config::ModuleConfig,
+    modules::{
+        keyboard::KeyboardModule,
+        menu::Menu,
+        registry::{Registration, Registry},
+    },
+};
+
+thread_local! {
+    /// Keys typed on the host by the test running on this thread.
+    static TYPED: RefCell<Vec<(Key, Direction)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records keys instead of typing them.
+struct Recorder;
+
+impl Keyboard for Recorder {
+    fn fast_text(&mut self, _text: &str) -> InputResult<Option<()>> {
+        Ok(None)
+    }
+
+    fn key(&mut self, key: Key, direction: Direction) -> InputResult<()> {
+        TYPED.with_borrow_mut(|x| x.push((key, direction)));
+        Ok(())
+    }
+
+    fn raw(&mut self, _keycode: u16, _direction: Direction) -> InputResult<()> {
+        Ok(())
+    }
+}
+
+/// Opens the keyboard module and returns what typing `keys` in it sends.
+async fn typed(keys: &[u8]) -> Vec<(Key, Direction)> {
+    let mut registry = Registry::new();
+    registry.register(Registration {
+        new: |_| Ok(Box::new(KeyboardModule::with_keyboard(Recorder))),
+        ..KeyboardModule::REGISTRATION
+    });
+    let entries = vec![ModuleConfig::new("keyboard")];
+    let mut harness = Harness::with_menu(Menu::new(registry, entries).unwrap()).await;
+
+    harness.press(b"\r").await;
+    harness.press(keys).await;
+    TYPED.take()
+}
+
+/// CTRL+the letter, as typed on the host.
+fn ctrl(letter: char) -> Vec<(Key, Direction)> {
+    vec![
+        (Key::Control, Direction::Press),
+        (Key::Unicode(letter), Direction::Click),
+        (Key::Control, Direction::Release),
+    ]
+}
+
+#[tokio::test]
+async fn ctrl_letters() {
+    // Also CTRL+down and SHIFT+up, but more useful as letters on the host
+    assert_eq!(typed(&[0x1A]).await, ctrl('z'));
+    assert_eq!(typed(&[0x14]).await, ctrl('t'));
+    assert_eq!(typed(&[0x05]).await, ctrl('e'));
+
+    assert_eq!(
+        typed(&[0x1F]).await,
+        vec![(Key::DownArrow, Direction::Click)]
+    );
+}
+
+#[tokio::test]
+async fn global_shortcuts_reach_the_host() {
+    assert_eq!(typed(&[0x10]).await, ctrl('p'));
+    assert_eq!(typed(&[0x0B, 0x04]).await, [ctrl('k'), ctrl('d')].concat());
+
+    // GRAPH+Q still goes back to the menu
+    assert_eq!(typed(&[0x93, b'a']).await, []);
+}
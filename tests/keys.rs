@@ -0,0 +1,84 @@
+use model_100_serial::{
+    config::Config,
+    key::{KeyCode, KeyEvent, KeyModifiers},
+    keymap::{Action, Keymap},
+};
+
+#[test]
+fn round_trip() {
+    for byte in 0x00..=0xFF {
+        assert_eq!(KeyEvent::decode(byte).encode(), Some(byte), "{byte:#X}");
+    }
+}
+
+#[test]
+fn decode() {
+    let key = |code, modifiers| KeyEvent::new(code, modifiers);
+    assert_eq!(KeyEvent::decode(b'a'), KeyCode::Char(b'a').into());
+    assert_eq!(KeyEvent::decode(0x0D), KeyCode::Enter.into());
+    assert_eq!(KeyEvent::decode(0x1D), KeyCode::Left.into());
+    assert_eq!(KeyEvent::decode(0x03), KeyCode::Break.into());
+
+    // Arrow combinations take precedence over the letters sharing their codes
+    assert_eq!(
+        KeyEvent::decode(0x01),
+        key(KeyCode::Left, KeyModifiers::SHIFT)
+    );
+    assert_eq!(
+        KeyEvent::decode(0x1A),
+        key(KeyCode::Down, KeyModifiers::CTRL)
+    );
+    assert_eq!(KeyEvent::decode(0x0C), KeyEvent::ctrl(b'l'));
+    assert_eq!(KeyEvent::ctrl(b'a').encode(), None);
+
+    assert_eq!(
+        KeyEvent::decode(0x93),
+        key(KeyCode::Char(0x93), KeyModifiers::GRAPH)
+    );
+    assert_eq!(
+        KeyEvent::decode(0xC9),
+        key(KeyCode::Char(0xC9), KeyModifiers::CODE)
+    );
+    assert_eq!(KeyEvent::decode(0xC9).char(), Some(0xC9));
+    assert_eq!(KeyEvent::ctrl(b'l').char(), None);
+}
+
+#[test]
+fn parse() {
+    let parse = |x: &str| x.parse::<KeyEvent>().unwrap();
+    assert_eq!(parse("esc"), KeyCode::Esc.into());
+    assert_eq!(parse("q"), KeyCode::Char(b'q').into());
+    assert_eq!(parse("shift+q"), KeyCode::Char(b'Q').into());
+    assert_eq!(parse("CTRL+L"), KeyEvent::ctrl(b'l'));
+    assert_eq!(parse("0x93"), KeyEvent::decode(0x93));
+
+    // Comes out as the key sharing its code
+    assert_eq!(parse("ctrl+r"), KeyEvent::decode(0x12));
+    assert_eq!(parse("ctrl+r"), parse("ctrl+right"));
+
+    assert!("ctrl+enter".parse::<KeyEvent>().is_err());
+    assert!("alt+x".parse::<KeyEvent>().is_err());
+    assert!("f1".parse::<KeyEvent>().is_err());
+}
+
+#[test]
+fn keymap() {
+    let keymap = Keymap::default();
+    assert_eq!(keymap.action(KeyEvent::ctrl(b'd')), Some(Action::Redraw));
+    assert_eq!(keymap.action(KeyEvent::decode(0x12)), None);
+    assert_eq!(keymap.action(KeyEvent::ctrl(b'p')), Some(Action::Menu));
+    assert_eq!(keymap.action(KeyCode::Char(b'p').into()), None);
+
+    let config: Config = toml::from_str(
+        "[keymap]\nswitch = \"shift+up\"\nhelp = \"none\"\n\n\
+         [[module]]\nkind = \"keyboard\"\npassthrough = [\"redraw\"]\n",
+    )
+    .unwrap();
+    let switch = KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT);
+    assert_eq!(config.keymap.action(switch), Some(Action::Switch));
+    assert_eq!(config.keymap.action(KeyEvent::ctrl(b'n')), None);
+    assert_eq!(config.keymap.action(KeyEvent::ctrl(b'k')), None);
+    assert_eq!(config.modules[0].passthrough, [Action::Redraw]);
+
+    assert!(toml::from_str::<Config>("[keymap]\nmenu = \"ctrl+\"\n").is_err());
+}
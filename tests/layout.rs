@@ -0,0 +1,62 @@
+use model_100_serial::screen::{Wrap, Wrapped};
+
+fn lines(wrapped: &Wrapped) -> Vec<&str> {
+    (wrapped.lines.iter())
+        .map(|x| std::str::from_utf8(x).unwrap())
+        .collect()
+}
+
+#[test]
+fn wraps_words() {
+    let wrapped = Wrap::new(10).wrap(b"The quick brown fox jumps over");
+    assert_eq!(lines(&wrapped), ["The quick", "brown fox", "jumps over"]);
+    assert_eq!(wrapped.line_count(), 3);
+    assert!(!wrapped.overflow);
+}
+
+#[test]
+fn paragraphs() {
+    let text = b"First line\n\nSecond paragraph here\n";
+    let wrapped = Wrap::new(10).wrap(text);
+    assert_eq!(
+        lines(&wrapped),
+        ["First line", "", "Second", "paragraph", "here"]
+    );
+
+    let wrapped = Wrap::new(10).wrap(b"a\r\nb\r\n");
+    assert_eq!(lines(&wrapped), ["a", "b"]);
+
+    let wrapped = Wrap::new(10).wrap(b"a\r\n\r\nb");
+    assert_eq!(lines(&wrapped), ["a", "", "b"]);
+
+    let wrapped = Wrap::new(10).paragraphs(false).wrap(text);
+    assert_eq!(
+        lines(&wrapped),
+        ["First line", "Second", "paragraph", "here"]
+    );
+}
+
+#[test]
+fn long_words() {
+    let wrapped = Wrap::new(4).wrap(b"a abcdefghij b");
+    assert_eq!(lines(&wrapped), ["a", "abcd", "efgh", "ij b"]);
+    assert!(!wrapped.overflow);
+
+    let wrapped = Wrap::new(4).break_words(false).wrap(b"a abcdefghij b");
+    assert_eq!(lines(&wrapped), ["a", "abcd", "b"]);
+    assert!(wrapped.overflow);
+}
+
+#[test]
+fn overflow() {
+    let text = b"one two three four five six";
+    let wrapped = Wrap::new(9).height(2).wrap(text);
+    assert_eq!(lines(&wrapped), ["one two", "three"]);
+    assert!(wrapped.overflow);
+
+    let wrapped = Wrap::new(9).height(2).ellipsis(true).wrap(text);
+    assert_eq!(lines(&wrapped), ["one two", "three..."]);
+
+    let wrapped = Wrap::new(9).height(3).ellipsis(true).wrap(text);
+    assert_eq!(lines(&wrapped), ["one two", "three", "four f..."]);
+}
@@ -0,0 +1,217 @@
+use model_100_serial::{
+    emulator::Emulator,
+    screen::{Char, Screen},
+};
+use nalgebra::Vector2;
+
+/// Small xorshift generator so the test is reproducible without extra deps.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: usize) -> usize {
+        (self.next() % max as u64) as usize
+    }
+
+    fn char(&mut self) -> Char {
+        let chr = [b' ', b'a', b'Z', b'#', 0xF1, 0xE9][self.below(6)];
+        Char::new(chr, self.below(4) == 0)
+    }
+}
+
+fn mutate(rng: &mut Rng, screen: &mut Screen) {
+    match rng.below(6) {
+        0 => screen.clear(),
+        1 => {
+            let pos = Vector2::new(rng.below(Screen::WIDTH), rng.below(Screen::HEIGHT));
+            let size = Vector2::new(rng.below(Screen::WIDTH), rng.below(Screen::HEIGHT));
+            screen.rect(pos, size, rng.char());
+        }
+        5 => {
+            let top = rng.below(Screen::HEIGHT);
+            let bottom = top + rng.below(Screen::HEIGHT - top);
+            let lines = rng.below(5) as isize - 2;
+            screen.scroll_region(top, bottom, lines);
+        }
+        2 => {
+            let pos = Vector2::new(rng.below(Screen::WIDTH), rng.below(Screen::HEIGHT));
+            screen.write_string_inverted(pos, b"Hello World", rng.below(2) == 0);
+        }
+        _ => {
+            for _ in 0..rng.below(20) {
+                let pos = Vector2::new(rng.below(Screen::WIDTH), rng.below(Screen::HEIGHT));
+                screen.put(pos, rng.char());
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn draw_matches_emulator() {
+    let mut rng = Rng(0x5EED_1983);
+    let mut screen = Screen::new();
+    let mut emulator = Emulator::new();
+    emulator.feed(b"\x0C\x1Bq\x1BV\x1BQ");
+
+    for frame in 0..500 {
+        mutate(&mut rng, &mut screen);
+
+        let mut out = Vec::new();
+        if frame % 50 == 49 {
+            screen.redraw(&mut out).await.unwrap();
+        } else {
+            screen.draw(&mut out).await.unwrap();
+        }
+
+        emulator.feed(&out);
+        assert!(
+            emulator.chars() == screen.chars(),
+            "frame {frame} diverged\n{}",
+            emulator.snapshot()
+        );
+    }
+}
+
+#[tokio::test]
+async fn cursor_jumps_are_cheap() {
+    let mut screen = Screen::new();
+    let mut out = Vec::new();
+
+    screen.put(Vector2::new(0, 0), b'a'.into());
+    screen.put(Vector2::new(39, 0), b'b'.into());
+    screen.draw(&mut out).await.unwrap();
+    assert_eq!(out, b"a\x1BY\x20\x47b");
+
+    out.clear();
+    screen.put(Vector2::new(0, 1), b'c'.into());
+    screen.put(Vector2::new(0, 3), b'd'.into());
+    screen.draw(&mut out).await.unwrap();
+    assert_eq!(out, b"c\r\n\nd");
+
+    let stats = screen.stats();
+    assert_eq!(stats.written, out.len() + 6);
+    assert_eq!(stats.saved, 72 + 3);
+}
+
+#[tokio::test]
+async fn short_gaps_are_retyped() {
+    let mut screen = Screen::new();
+    let mut out = Vec::new();
+
+    screen.write_string(Vector2::new(0, 0), b"abcdef");
+    screen.draw(&mut out).await.unwrap();
+
+    out.clear();
+    screen.put(Vector2::new(0, 0), b'A'.into());
+    screen.put(Vector2::new(2, 0), b'C'.into());
+    screen.draw(&mut out).await.unwrap();
+    assert_eq!(out, b"\rAbC");
+}
+
+#[tokio::test]
+async fn retyped_gaps_count_inversion() {
+    let mut screen = Screen::new();
+    let mut out = Vec::new();
+
+    screen.put(Vector2::new(0, 0), b'a'.into());
+    screen.write_string_inverted(Vector2::new(1, 0), b"bc", true);
+    screen.draw(&mut out).await.unwrap();
+
+    // Retyping the inverted gap saves switching back to inverted for `C`
+    out.clear();
+    screen.put(Vector2::new(0, 0), b'A'.into());
+    screen.put(Vector2::new(2, 0), Char::new(b'C', true));
+    screen.draw(&mut out).await.unwrap();
+    assert_eq!(out, b"\r\x1BqA\x1BpbC");
+}
+
+#[tokio::test]
+async fn blank_tails_are_erased() {
+    let mut screen = Screen::new();
+    let mut out = Vec::new();
+
+    screen.write_string(Vector2::new(0, 2), b"Hello World, how are you?");
+    screen.draw(&mut out).await.unwrap();
+
+    out.clear();
+    screen.rect(Vector2::new(5, 2), Vector2::new(35, 1), b' '.into());
+    screen.draw(&mut out).await.unwrap();
+    assert_eq!(out, b"\x1BY\x22\x26\x1BK");
+
+    out.clear();
+    screen.clear();
+    screen.draw(&mut out).await.unwrap();
+    assert_eq!(out, b"\x1Bl");
+}
+
+#[tokio::test]
+async fn scrolling_sends_only_the_new_line() {
+    let mut screen = Screen::new();
+    let mut emulator = Emulator::new();
+    let mut out = Vec::new();
+
+    let log = |screen: &mut Screen, start: usize| {
+        screen.clear();
+        for y in 0..Screen::HEIGHT {
+            let words = ["alpha", "bravo", "charlie", "delta", "echo"];
+            let line = format!("{} {}", words[(start + y) % 5], start + y);
+            screen.write_string(Vector2::new(0, y), line.as_bytes());
+        }
+    };
+
+    log(&mut screen, 0);
+    screen.draw(&mut out).await.unwrap();
+    emulator.feed(&out);
+
+    // Whole screen, detected without a hint
+    out.clear();
+    log(&mut screen, 1);
+    screen.draw(&mut out).await.unwrap();
+    emulator.feed(&out);
+    assert!(out.len() <= 14, "{out:?}");
+    assert!(emulator.chars() == screen.chars());
+
+    // Region above a status line
+    out.clear();
+    screen.scroll_region(1, 6, -2);
+    screen.write_string(Vector2::new(0, 1), b"Inserted");
+    screen.draw(&mut out).await.unwrap();
+    emulator.feed(&out);
+    assert!(out.len() < 30, "{out:?}");
+    assert!(emulator.chars() == screen.chars());
+}
+
+#[tokio::test]
+async fn repeated_scrolls_are_combined() {
+    let mut screen = Screen::new();
+    let mut emulator = Emulator::new();
+    let mut out = Vec::new();
+    let line = |y: usize| {
+        let words = ["alpha", "bravo", "charlie", "delta", "echo"];
+        format!("{} {y}", words[y % 5].repeat(3))
+    };
+
+    for y in 0..Screen::HEIGHT - 1 {
+        screen.write_string(Vector2::new(0, y), line(y).as_bytes());
+    }
+    screen.write_string(Vector2::new(0, 7), b"Status");
+    screen.draw(&mut out).await.unwrap();
+    emulator.feed(&out);
+
+    // Two lines added above the status line before the next draw
+    out.clear();
+    for y in 7..9 {
+        screen.scroll_region(0, 6, 1);
+        screen.write_string(Vector2::new(0, 6), line(y).as_bytes());
+    }
+    screen.draw(&mut out).await.unwrap();
+    emulator.feed(&out);
+    assert!(out.len() < 60, "{out:?}");
+    assert!(emulator.chars() == screen.chars());
+}
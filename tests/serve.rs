@@ -0,0 +1,129 @@
+mod common;
+
+use std::{
+    env, fs, future, process,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use model_100_serial::{
+    config::{ModuleConfig, SerialConfig},
+    emulator::Emulator,
+    key::KeyEvent,
+    modules::{
+        Module,
+        menu::Menu,
+        registry::{Registration, Registry},
+    },
+    serve,
+    state::State,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+    sync::oneshot,
+    time,
+};
+
+static EXITED: AtomicBool = AtomicBool::new(false);
+
+/// Records that its exit hook ran, and refuses to exit.
+struct Job;
+
+#[async_trait]
+impl Module for Job {
+    async fn init(&mut self, _screen: &mut State) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_key(&mut self, _screen: &mut State, _key: KeyEvent) -> Result<()> {
+        Ok(())
+    }
+
+    async fn on_exit(&mut self, _screen: &mut State) -> Result<bool> {
+        EXITED.store(true, Ordering::SeqCst);
+        Ok(false)
+    }
+}
+
+/// Feeds everything the bridge sends until it goes quiet.
+async fn receive(device: &mut UnixStream, emulator: &mut Emulator) {
+    let mut buf = [0; 1024];
+    while let Ok(read) = time::timeout(Duration::from_millis(100), device.read(&mut buf)).await {
+        let read = read.unwrap();
+        if read == 0 {
+            break;
+        }
+        emulator.feed(&buf[..read]);
+    }
+}
+
+/// Listens on a fresh Unix socket and returns settings to connect to it.
+fn listen(name: &str) -> (UnixListener, SerialConfig) {
+    let path = env::temp_dir().join(format!("model-100-{name}-{}.sock", process::id()));
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let serial = SerialConfig {
+        port: format!("unix:{}", path.display()),
+        ..Default::default()
+    };
+    (listener, serial)
+}
+
+#[tokio::test]
+async fn reconnects() {
+    let (listener, serial) = listen("reconnect");
+    let bridge =
+        tokio::spawn(async move { serve(&serial, &mut Menu::default(), future::pending()).await });
+
+    let (mut device, _) = listener.accept().await.unwrap();
+    let mut emulator = Emulator::new();
+    device.write_all(&[0x1F]).await.unwrap();
+    receive(&mut device, &mut emulator).await;
+    common::assert_snapshot("menu_printer_selected", &emulator.snapshot());
+
+    // The new connection gets the whole screen, with the selection kept
+    drop(device);
+    let (mut device, _) = listener.accept().await.unwrap();
+    let mut emulator = Emulator::new();
+    receive(&mut device, &mut emulator).await;
+    common::assert_snapshot("menu_printer_selected", &emulator.snapshot());
+
+    bridge.abort();
+}
+
+#[tokio::test]
+async fn shuts_down() {
+    let (listener, serial) = listen("shutdown");
+    let (stop, stopped) = oneshot::channel::<()>();
+    let bridge = tokio::spawn(async move {
+        let shutdown = async {
+            let _ = stopped.await;
+        };
+        let mut registry = Registry::default();
+        registry.register(Registration {
+            kind: "job",
+            name: "Job",
+            description: "Runs until shut down",
+            hotkey: None,
+            new: |_| Ok(Box::new(Job)),
+        });
+        let mut menu = Menu::new(registry, vec![ModuleConfig::new("job")]).unwrap();
+        serve(&serial, &mut menu, shutdown).await
+    });
+
+    let (mut device, _) = listener.accept().await.unwrap();
+    let mut emulator = Emulator::new();
+    device.write_all(b"\r").await.unwrap();
+    receive(&mut device, &mut emulator).await;
+
+    // Closed even though it refuses
+    stop.send(()).unwrap();
+    bridge.await.unwrap().unwrap();
+    assert!(EXITED.load(Ordering::SeqCst));
+    receive(&mut device, &mut emulator).await;
+    common::assert_snapshot("offline", &emulator.snapshot());
+}
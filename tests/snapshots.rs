@@ -0,0 +1,472 @@
+mod common;
+
+use std::{env, fs, process, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use common::Harness;
+use futures::FutureExt;
+use model_100_serial::{
+    config::{Config, ModuleConfig},
+    key::{KeyCode, KeyEvent},
+    keymap::{Action, Keymap},
+    modules::{
+        Module,
+        menu::Menu,
+        printer::PrinterModule,
+        registry::{Registration, Registry},
+    },
+    state::State,
+    timer::TimerEvent,
+    widgets::dialog,
+};
+use nalgebra::Vector2;
+use printers::common::base::printer::{Printer, PrinterState};
+use tokio::time;
+
+#[tokio::test]
+async fn menu() {
+    let mut harness = Harness::new().await;
+    harness.assert_snapshot("menu");
+
+    harness.press(&[0x1F, 0x1F]).await;
+    harness.assert_snapshot("menu_keyboard_selected");
+
+    harness.press(&[0x1E]).await;
+    harness.assert_snapshot("menu_printer_selected");
+
+    // Wraps around past either end
+    harness.press(&[0x1E, 0x1E]).await;
+    harness.assert_snapshot("menu_exit_selected");
+    harness.press(&[0x1F]).await;
+    harness.assert_snapshot("menu");
+}
+
+#[tokio::test]
+async fn menu_hotkeys() {
+    let mut harness = Harness::new().await;
+    harness.press(b"c").await;
+    harness.assert_snapshot("chatgpt");
+}
+
+#[tokio::test]
+async fn redraw() {
+    let mut harness = Harness::new().await;
+    harness.press(&[0x1F]).await;
+
+    let before = harness.emulator.snapshot();
+    harness.press(&[0x04]).await;
+    assert_eq!(before, harness.emulator.snapshot());
+}
+
+#[tokio::test]
+async fn chatgpt() {
+    let mut harness = Harness::new().await;
+    harness.press(b"\r").await;
+    harness.assert_snapshot("chatgpt");
+
+    harness.press(b"Hello\x08").await;
+    harness.assert_snapshot("chatgpt_typing");
+
+    harness.press(b"\x1B").await;
+    harness.assert_snapshot("menu");
+}
+
+#[tokio::test]
+async fn chatgpt_editing() {
+    let mut harness = Harness::new().await;
+    harness.press(b"\rHello world").await;
+
+    // Word left and right with CTRL+arrows, start and end with SHIFT+arrows
+    harness.press(b"\x11big ").await;
+    harness.press(b"\x01Oh ").await;
+    harness.press(b"\x12very ").await;
+    harness.press(b"\x06!").await;
+    harness.assert_snapshot("chatgpt_editing");
+}
+
+#[tokio::test]
+async fn configured_menu() {
+    let path = env::temp_dir().join(format!("model-100-menu-{}.toml", process::id()));
+    let write = |order: &str| {
+        let config = format!(
+            "{order}\n[module.settings]\napi_key = \"test\"\nmodel = \"gpt-4o\"\n\n[[module]]\nkind = \"keyboard\"\n"
+        );
+        fs::write(&path, config).unwrap();
+    };
+
+    write("[[module]]\nkind = \"chatgpt\"\nname = \"Ask\"");
+    let config = Config::read(&path).unwrap();
+    let mut harness = Harness::with_menu(
+        Menu::new(Registry::default(), config.modules)
+            .unwrap()
+            .reload_from(path.clone()),
+    )
+    .await;
+    harness.assert_snapshot("menu_configured");
+
+    // Reloading keeps the selected position
+    write("[[module]]\nkind = \"printer\"\n[[module]]\nkind = \"chatgpt\"");
+    harness.press(&[0x1F, 0x0C]).await;
+    harness.assert_snapshot("menu_reloaded");
+
+    // Errors are shown without losing the entries
+    fs::write(&path, "[[module]]\nkind = \"toaster\"\n").unwrap();
+    harness.send(b"\r").await;
+    harness.press(&[0x0C]).await;
+    harness.assert_snapshot("menu_reloaded");
+
+    let _ = fs::remove_file(&path);
+}
+
+/// Shows the last character typed, and asks before exiting with Esc.
+struct Echo;
+
+#[async_trait]
+impl Module for Echo {
+    async fn init(&mut self, screen: &mut State) -> Result<()> {
+        screen.write_string(Vector2::zeros(), b"Echo");
+        screen.draw().await
+    }
+
+    async fn on_key(&mut self, screen: &mut State, key: KeyEvent) -> Result<()> {
+        if key.code == KeyCode::Esc {
+            screen.exit();
+            return Ok(());
+        }
+
+        if let Some(chr) = key.char() {
+            screen.put(Vector2::new(0, 1), chr.into());
+        }
+        screen.draw().await
+    }
+
+    async fn on_exit(&mut self, screen: &mut State) -> Result<bool> {
+        dialog::confirm(screen, b"Close echo?").await
+    }
+}
+
+/// Counts how often its timer fired, and exits with Esc.
+struct Ticker(u32);
+
+impl Ticker {
+    async fn draw(&self, screen: &mut State) -> Result<()> {
+        let message = format!("Ticks: {}", self.0);
+        screen.write_string(Vector2::zeros(), message.as_bytes());
+        screen.draw().await
+    }
+}
+
+#[async_trait]
+impl Module for Ticker {
+    async fn init(&mut self, screen: &mut State) -> Result<()> {
+        screen.repeat(Duration::from_secs(1), 1_u32);
+        self.draw(screen).await
+    }
+
+    async fn on_key(&mut self, screen: &mut State, key: KeyEvent) -> Result<()> {
+        if key.code == KeyCode::Esc {
+            screen.exit();
+        }
+        Ok(())
+    }
+
+    async fn callback(&mut self, screen: &mut State, event: TimerEvent) -> Result<()> {
+        self.0 += event.payload::<u32>().unwrap();
+        self.draw(screen).await
+    }
+}
+
+/// Takes every key as data, like the printer receiving a file.
+struct Upload(Vec<u8>);
+
+#[async_trait]
+impl Module for Upload {
+    async fn init(&mut self, screen: &mut State) -> Result<()> {
+        screen.write_string(Vector2::zeros(), b"Waiting for upload");
+        screen.draw().await
+    }
+
+    async fn on_key(&mut self, screen: &mut State, key: KeyEvent) -> Result<()> {
+        self.0.extend(key.encode());
+        let hex: String = self.0.iter().map(|x| format!("{x:02X}")).collect();
+        screen.clear();
+        screen.write_string(Vector2::zeros(), hex.as_bytes());
+        screen.draw().await
+    }
+
+    fn raw_input(&self) -> bool {
+        true
+    }
+}
+
+fn echo_registry() -> Registry {
+    let mut registry = Registry::default();
+    registry.register(Registration {
+        kind: "echo",
+        name: "Echo",
+        description: "Repeats keys",
+        hotkey: Some(b'x'),
+        new: |_| Ok(Box::new(Echo)),
+    });
+    registry.register(Registration {
+        kind: "ticker",
+        name: "Ticker",
+        description: "Counts seconds",
+        hotkey: Some(b't'),
+        new: |_| Ok(Box::new(Ticker(0))),
+    });
+    registry.register(Registration {
+        kind: "upload",
+        name: "Upload",
+        description: "Receives a file",
+        hotkey: Some(b'u'),
+        new: |_| Ok(Box::new(Upload(Vec::new()))),
+    });
+    registry
+}
+
+#[tokio::test]
+async fn registered_module() {
+    let registry = echo_registry();
+
+    let mut keyboard = ModuleConfig::new("keyboard");
+    keyboard.enabled = false;
+    let entries = vec![
+        ModuleConfig::new("echo"),
+        keyboard,
+        ModuleConfig::new("printer"),
+    ];
+    let mut harness = Harness::with_menu(Menu::new(registry.clone(), entries).unwrap()).await;
+    harness.assert_snapshot("menu_registered");
+
+    harness.press(b"xq").await;
+    harness.assert_snapshot("echo");
+
+    let unknown = vec![ModuleConfig::new("toaster")];
+    assert!(Menu::new(registry.clone(), unknown).is_err());
+
+    // Running modules couldn't tell which of two identical entries is theirs
+    let twice = vec![ModuleConfig::new("echo"), ModuleConfig::new("echo")];
+    let err = Menu::new(registry.clone(), twice).err().unwrap();
+    assert!(err.to_string().contains("`Echo` is listed twice"), "{err}");
+
+    let mut renamed = ModuleConfig::new("echo");
+    renamed.name = Some("Echo 2".into());
+    assert!(Menu::new(registry, vec![ModuleConfig::new("echo"), renamed]).is_ok());
+}
+
+#[tokio::test]
+async fn suspend_and_resume() {
+    let entries = vec![ModuleConfig::new("echo"), ModuleConfig::new("printer")];
+    let mut harness = Harness::with_menu(Menu::new(echo_registry(), entries).unwrap()).await;
+    harness.press(b"xq").await;
+
+    // CTRL+P sends it to the background with its screen intact
+    harness.press(&[0x10]).await;
+    harness.assert_snapshot("menu_running");
+    harness.press(b"x").await;
+    harness.assert_snapshot("echo");
+
+    // Saying no to the exit keeps it open
+    harness.send(b"n").await;
+    harness.press(b"\x1B").await;
+    harness.assert_snapshot("echo");
+
+    harness.send(b"y").await;
+    harness.press(b"\x1B").await;
+    harness.assert_snapshot("menu_resumed_closed");
+}
+
+#[tokio::test]
+async fn switch_modules() {
+    let entries = vec![ModuleConfig::new("ticker"), ModuleConfig::new("echo")];
+    let mut harness = Harness::with_menu(Menu::new(echo_registry(), entries).unwrap()).await;
+    harness.press(b"t").await;
+    harness.assert_snapshot("ticker");
+
+    // Its timer keeps running in the background without touching the screen
+    harness.press(&[0x10]).await;
+    let menu = harness.emulator.snapshot();
+    harness.fire().await;
+    assert_eq!(menu, harness.emulator.snapshot());
+
+    // CTRL+N cycles through the running modules
+    harness.press(b"xq").await;
+    harness.press(&[0x0E]).await;
+    harness.assert_snapshot("ticker_ticked");
+    harness.press(&[0x0E]).await;
+    harness.assert_snapshot("echo");
+
+    // Its timers go away when it exits
+    harness.send(b"y").await;
+    harness.press(b"\x1B").await;
+    harness.press(b"t\x1B").await;
+    assert!(harness.state.next_timeout().is_none());
+}
+
+#[tokio::test]
+async fn reload_closes_removed() {
+    let path = env::temp_dir().join(format!("model-100-removed-{}.toml", process::id()));
+    fs::write(
+        &path,
+        "[[module]]\nkind = \"ticker\"\n[[module]]\nkind = \"echo\"",
+    )
+    .unwrap();
+    let entries = Config::read(&path).unwrap().modules;
+    let menu = Menu::new(echo_registry(), entries)
+        .unwrap()
+        .reload_from(path.clone());
+    let mut harness = Harness::with_menu(menu).await;
+
+    harness.press(b"t").await;
+    harness.press(&[0x10]).await;
+    assert!(harness.state.next_timeout().is_some());
+
+    // Its repeating timer goes with it
+    fs::write(&path, "[[module]]\nkind = \"echo\"").unwrap();
+    harness.press(&[0x0C]).await;
+    harness.assert_snapshot("menu_removed");
+    assert!(harness.state.next_timeout().is_none());
+
+    let _ = fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn reload_in_module() {
+    let path = env::temp_dir().join(format!("model-100-in-module-{}.toml", process::id()));
+    fs::write(
+        &path,
+        "[[module]]\nkind = \"echo\"\n[[module]]\nkind = \"ticker\"",
+    )
+    .unwrap();
+    let entries = Config::read(&path).unwrap().modules;
+    let menu = Menu::new(echo_registry(), entries)
+        .unwrap()
+        .reload_from(path.clone());
+    let mut harness = Harness::with_menu(menu).await;
+
+    // The module on screen keeps it after the reload
+    harness.press(b"xq").await;
+    fs::write(&path, "[[module]]\nkind = \"echo\"").unwrap();
+    harness.press(&[0x0C]).await;
+    harness.assert_snapshot("echo");
+    let _ = fs::remove_file(&path);
+
+    let saved = *harness.state.chars();
+    let help = KeyEvent::ctrl(b'k');
+    let pending = harness.menu.on_key(&mut harness.state, help).now_or_never();
+    assert!(pending.is_none());
+    harness.sync();
+    harness.assert_snapshot("help_reload");
+    harness.state.restore(&saved);
+}
+
+#[tokio::test]
+async fn raw_input() {
+    let entries = vec![ModuleConfig::new("upload")];
+    let mut harness = Harness::with_menu(Menu::new(echo_registry(), entries).unwrap()).await;
+
+    // Bytes bound to global shortcuts are part of the file
+    harness.press(b"u").await;
+    harness
+        .press(&[b'a', 0x04, 0x0B, 0x0E, 0x0F, 0x10, 0x12, b'z'])
+        .await;
+    harness.assert_snapshot("upload");
+}
+
+/// Only the printer module, with a made-up printer.
+fn printer_registry() -> Registry {
+    let mut registry = Registry::new();
+    registry.register(Registration {
+        new: |config| {
+            let printer = Printer {
+                name: "Desk".into(),
+                system_name: "desk".into(),
+                driver_name: String::new(),
+                uri: String::new(),
+                port_name: String::new(),
+                processor: String::new(),
+                data_type: String::new(),
+                description: String::new(),
+                location: String::new(),
+                is_default: true,
+                is_shared: false,
+                state: PrinterState::READY,
+                state_reasons: Vec::new(),
+            };
+            Ok(Box::new(PrinterModule::with_printers(
+                config,
+                vec![printer],
+            )?))
+        },
+        ..PrinterModule::REGISTRATION
+    });
+    registry
+}
+
+#[tokio::test]
+async fn printer_upload() {
+    let entries = vec![ModuleConfig::new("printer")];
+    let menu = Menu::new(printer_registry(), entries.clone()).unwrap();
+    let mut harness = Harness::with_menu(menu).await;
+
+    // ESC is part of the file once it's coming in. Were it taken as leaving
+    // instead, these answer the cancel question and the count comes up short
+    harness.send(b"nn").await;
+    harness.press(b"\r\r").await;
+    harness.press(b"# Hi\x1B[1m").await;
+    time::sleep(Duration::from_millis(250)).await;
+    harness.press(b"\x1B").await;
+    harness.assert_snapshot("printer_uploading");
+
+    // Before that it goes back to the menu
+    let mut harness = Harness::with_menu(Menu::new(printer_registry(), entries).unwrap()).await;
+    harness.press(b"\r\r\x1B").await;
+    harness.assert_snapshot("printer_menu");
+}
+
+#[tokio::test]
+async fn keymap() {
+    let dir = env::temp_dir().join(format!("model-100-screenshots-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let keymap = Keymap {
+        menu: "ctrl+x".to_owned().try_into().unwrap(),
+        screenshot_dir: dir.clone(),
+        ..Keymap::default()
+    };
+    let mut echo = ModuleConfig::new("echo");
+    echo.passthrough = vec![Action::Screenshot];
+    let entries = vec![echo, ModuleConfig::new("printer")];
+    let menu = Menu::new(echo_registry(), entries).unwrap().keymap(keymap);
+    let mut harness = Harness::with_menu(menu).await;
+
+    // CTRL+P isn't bound anymore, so it goes to the module
+    harness.press(b"xq").await;
+    harness.press(&[0x10]).await;
+    harness.assert_snapshot("echo");
+    harness.press(&[0x18]).await;
+    harness.assert_snapshot("menu_running");
+
+    // Passed through to Echo, which ignores it
+    harness.press(b"x").await;
+    harness.press(&[0x0F]).await;
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    harness.send(b"\r").await;
+    harness.press(&[0x18, 0x0F]).await;
+    let screenshot = fs::read_dir(&dir).unwrap().next().unwrap().unwrap();
+    let text = fs::read_to_string(screenshot.path()).unwrap();
+    assert!(text.contains("> Echo * <\n"), "{text}");
+    fs::remove_dir_all(&dir).unwrap();
+
+    // Waits for a key after drawing, so put the menu back by hand
+    let saved = *harness.state.chars();
+    let help = KeyEvent::ctrl(b'k');
+    let pending = harness.menu.on_key(&mut harness.state, help).now_or_never();
+    assert!(pending.is_none());
+    harness.sync();
+    harness.assert_snapshot("help");
+    harness.state.restore(&saved);
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use std::time::Duration;
+
+use common::Harness;
+
+#[tokio::test]
+async fn order_repeat_and_cancel() {
+    let mut harness = Harness::new().await;
+    let state = &mut harness.state;
+
+    let once = state.schedule(Duration::from_secs(3), "once");
+    let every = state.repeat(Duration::from_secs(2), 7_u32);
+    assert_ne!(once, every);
+
+    let event = state.pop_timeout().unwrap();
+    assert_eq!(event.timer, every);
+    assert_eq!(event.payload::<u32>(), Some(&7));
+    assert_eq!(event.payload::<&str>(), None);
+
+    // Back in for another run after the one-shot timer
+    let event = state.pop_timeout().unwrap();
+    assert_eq!(event.timer, once);
+    assert_eq!(event.payload::<&str>(), Some(&"once"));
+    assert_eq!(state.pop_timeout().unwrap().timer, every);
+
+    state.cancel(every);
+    assert!(state.pop_timeout().is_none());
+
+    state.schedule(Duration::from_secs(1), ());
+    state.repeat(Duration::from_secs(1), ());
+    state.cancel_all();
+    assert!(state.next_timeout().is_none());
+}
@@ -0,0 +1,215 @@
+use model_100_serial::{
+    key::KeyEvent,
+    screen::{Screen, glyph},
+    widgets::{
+        editor::{Edit, LineEditor},
+        list::{Item, List, Select},
+        viewer::Viewer,
+    },
+};
+use nalgebra::Vector2;
+
+/// Decodes a key the way the Model 100 sends it.
+fn key(byte: u8) -> KeyEvent {
+    KeyEvent::decode(byte)
+}
+
+fn row(screen: &Screen, y: usize) -> Vec<u8> {
+    screen.chars()[y * Screen::WIDTH..][..Screen::WIDTH]
+        .iter()
+        .map(|x| x.char())
+        .collect()
+}
+
+#[test]
+fn viewer_scrolls() {
+    let mut screen = Screen::new();
+    let mut viewer = Viewer::new();
+    viewer.set_text(b"one\ntwo\nthree\nfour\nfive\nsix".to_vec());
+
+    let draw = |screen: &mut Screen, viewer: &mut Viewer| {
+        let mut window = screen.window(Vector2::new(0, 0), Vector2::new(6, 3));
+        viewer.draw(&mut window);
+    };
+
+    // Follows the end of new text
+    draw(&mut screen, &mut viewer);
+    assert_eq!(&row(&screen, 0)[..6], b"four \xF5");
+    assert_eq!(
+        &row(&screen, 2)[..6],
+        [b"six  ".as_slice(), &[glyph::FULL_BLOCK]].concat()
+    );
+
+    assert!(viewer.on_key(key(0x17)));
+    draw(&mut screen, &mut viewer);
+    assert_eq!(
+        &row(&screen, 0)[..6],
+        [b"one  ".as_slice(), &[glyph::FULL_BLOCK]].concat()
+    );
+
+    assert!(viewer.on_key(key(0x1F)));
+    assert!(viewer.on_key(key(0x02)));
+    draw(&mut screen, &mut viewer);
+    assert_eq!(&row(&screen, 0)[..5], b"four ");
+
+    assert!(viewer.on_key(key(0x1E)));
+    draw(&mut screen, &mut viewer);
+    assert_eq!(&row(&screen, 0)[..5], b"three");
+
+    // Pushing while scrolled up doesn't move the view
+    viewer.push(b"\nseven");
+    draw(&mut screen, &mut viewer);
+    assert_eq!(&row(&screen, 0)[..5], b"three");
+
+    assert!(!viewer.on_key(key(b'a')));
+}
+
+fn submit(editor: &mut LineEditor) -> Vec<u8> {
+    match editor.on_key(key(0x0D)) {
+        Edit::Submit(text) => text,
+        _ => panic!("Enter didn't submit"),
+    }
+}
+
+#[test]
+fn editor_moves_and_edits() {
+    let mut editor = LineEditor::new(b">");
+    for &byte in b"hello world" {
+        editor.on_key(key(byte));
+    }
+
+    // Word left, then insert before "world"
+    editor.on_key(key(0x11));
+    editor.on_key(key(b'_'));
+    assert_eq!(editor.text(), b"hello _world");
+
+    editor.on_key(key(0x1D));
+    editor.on_key(key(0x08));
+    assert_eq!(editor.text(), b"hello_world");
+
+    editor.on_key(key(0x01));
+    editor.on_key(key(0x7F));
+    editor.on_key(key(0x06));
+    editor.on_key(key(b'!'));
+    assert_eq!(editor.text(), b"ello_world!");
+
+    assert!(matches!(editor.on_key(key(0x1B)), Edit::Ignored));
+    assert_eq!(submit(&mut editor), b"ello_world!");
+    assert_eq!(editor.text(), b"");
+}
+
+#[test]
+fn editor_history() {
+    let mut editor = LineEditor::new(b"");
+    for line in [b"one", b"two"] {
+        editor.set_text(line.to_vec());
+        submit(&mut editor);
+    }
+
+    editor.set_text(b"draft".to_vec());
+    editor.on_key(key(0x1E));
+    assert_eq!(editor.text(), b"two");
+    editor.on_key(key(0x1E));
+    editor.on_key(key(0x1E));
+    assert_eq!(editor.text(), b"one");
+
+    editor.on_key(key(0x1F));
+    editor.on_key(key(0x1F));
+    assert_eq!(editor.text(), b"draft");
+}
+
+#[test]
+fn editor_scrolls_and_wraps() {
+    let mut screen = Screen::new();
+    let mut editor = LineEditor::new(b">");
+    editor.set_text(b"abcdefghij".to_vec());
+
+    // The cursor at the end is kept in view
+    let mut window = screen.window(Vector2::zeros(), Vector2::new(6, 1));
+    editor.draw(&mut window);
+    assert_eq!(&row(&screen, 0)[..6], b"fghij ");
+    assert!(screen.chars()[5].inverted());
+
+    editor.on_key(key(0x01));
+    let mut window = screen.window(Vector2::zeros(), Vector2::new(6, 1));
+    editor.draw(&mut window);
+    assert_eq!(&row(&screen, 0)[..6], b"abcdef");
+    assert!(screen.chars()[0].inverted());
+
+    let mut editor = LineEditor::new(b">").multiline(true);
+    editor.set_text(b"abcdefghij".to_vec());
+    assert_eq!(editor.rows(6), 2);
+
+    let mut window = screen.window(Vector2::zeros(), Vector2::new(6, 2));
+    editor.draw(&mut window);
+    assert_eq!(&row(&screen, 0)[..6], b">abcde");
+    assert_eq!(&row(&screen, 1)[..6], b"fghij ");
+
+    // Up moves between rows before reaching the history
+    editor.on_key(key(0x1E));
+    editor.on_key(key(b'_'));
+    assert_eq!(editor.text(), b"abcd_efghij");
+}
+
+#[test]
+fn list_selection() {
+    let mut list = List::new(vec![
+        Item::new(b"Apple").enabled(false),
+        Item::new(b"Banana"),
+        Item::new(b"Cherry"),
+        Item::new(b"Blueberry"),
+    ]);
+    assert_eq!(list.selected(), Some(1));
+
+    // Wraps around, skipping disabled items
+    list.on_key(key(0x1E));
+    assert_eq!(list.selected(), Some(3));
+    list.on_key(key(0x1F));
+    assert_eq!(list.selected(), Some(1));
+
+    // Shared letters cycle, unique ones choose
+    assert!(matches!(list.on_key(key(b'b')), Select::Moved));
+    assert_eq!(list.selected(), Some(3));
+    assert!(matches!(list.on_key(key(b'C')), Select::Chosen(2)));
+    assert!(matches!(list.on_key(key(b'a')), Select::Ignored));
+    assert!(matches!(list.on_key(key(0x0D)), Select::Chosen(2)));
+
+    let mut empty = List::new(Vec::new());
+    assert_eq!(empty.selected(), None);
+    assert!(matches!(empty.on_key(key(0x0D)), Select::Ignored));
+}
+
+#[test]
+fn list_scrolls() {
+    let mut screen = Screen::new();
+    let labels = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+    let mut list = List::new(labels.iter().map(|x| Item::new(x.as_bytes())).collect());
+
+    let draw = |screen: &mut Screen, list: &mut List| {
+        let mut window = screen.window(Vector2::zeros(), Vector2::new(Screen::WIDTH, 8));
+        list.draw(&mut window);
+    };
+
+    draw(&mut screen, &mut list);
+    assert_eq!(row(&screen, 0)[19], b'a');
+    assert_eq!(row(&screen, 0)[39], glyph::FULL_BLOCK);
+
+    list.on_key(key(0x1E));
+    draw(&mut screen, &mut list);
+    assert_eq!(row(&screen, 0)[19], b'c');
+    assert_eq!(row(&screen, 7)[19], b'j');
+    assert!(screen.chars()[7 * Screen::WIDTH + 19].inverted());
+    assert_eq!(row(&screen, 7)[39], glyph::FULL_BLOCK);
+}
+
+#[test]
+fn list_without_room() {
+    let mut screen = Screen::new();
+    let labels = ["a", "b", "c"];
+    let mut list = List::new(labels.iter().map(|x| Item::new(x.as_bytes())).collect());
+
+    for size in [Vector2::new(0, 2), Vector2::new(Screen::WIDTH, 0)] {
+        list.draw(&mut screen.window(Vector2::zeros(), size));
+    }
+    assert!(row(&screen, 0).iter().all(|x| *x == b' '));
+}
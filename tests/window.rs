@@ -0,0 +1,82 @@
+use model_100_serial::screen::{Char, FrameStyle, Screen, glyph};
+use nalgebra::Vector2;
+
+fn row(screen: &Screen, y: usize) -> String {
+    screen.chars()[y * Screen::WIDTH..][..Screen::WIDTH]
+        .iter()
+        .map(|x| x.char() as char)
+        .collect()
+}
+
+#[test]
+fn clips_to_bounds() {
+    let mut screen = Screen::new();
+    let mut window = screen.window(Vector2::new(2, 1), Vector2::new(5, 2));
+    window.rect(Vector2::zeros(), Vector2::new(10, 10), b'.'.into());
+    window.write_string(Vector2::new(3, 0), b"Hello");
+    window.write_string_wrapped(Vector2::zeros(), b"ab cd ef");
+
+    assert_eq!(row(&screen, 0).trim_end(), "");
+    assert_eq!(row(&screen, 1).trim_end(), "  ab cd");
+    assert_eq!(row(&screen, 2).trim_end(), "  ef...");
+    assert_eq!(row(&screen, 3).trim_end(), "");
+}
+
+#[test]
+fn nested_windows() {
+    let mut screen = Screen::new();
+    let mut outer = screen.window(Vector2::new(30, 6), Vector2::new(20, 20));
+    assert_eq!(outer.size(), Vector2::new(10, 2));
+
+    let mut inner = outer.window(Vector2::new(8, 1), Vector2::new(5, 5));
+    assert_eq!(inner.origin(), Vector2::new(38, 7));
+    inner.write_string(Vector2::zeros(), b"xyz");
+    inner.put(Vector2::new(0, 1), Char::from(b'!'));
+
+    assert_eq!(row(&screen, 7).trim_end(), format!("{:38}xy", ""));
+}
+
+#[test]
+fn frames() {
+    let mut screen = Screen::new();
+    let mut inner = screen.frame_titled(
+        Vector2::new(1, 1),
+        Vector2::new(12, 4),
+        FrameStyle::Single,
+        b"Title",
+    );
+    assert_eq!(inner.size(), Vector2::new(10, 2));
+    inner.write_string(Vector2::zeros(), b"Inside the frame");
+    screen.divider(Vector2::new(1, 3), 12, FrameStyle::Single);
+
+    let glyphs = |y| row(&screen, y).chars().skip(1).take(12).collect::<String>();
+    let line = |c: u8| (c as char).to_string();
+    let edge = line(glyph::HORIZONTAL);
+    assert_eq!(
+        glyphs(1),
+        format!(
+            "{}{} Title {}{}",
+            line(glyph::TOP_LEFT),
+            edge,
+            edge.repeat(2),
+            line(glyph::TOP_RIGHT)
+        )
+    );
+    assert_eq!(
+        glyphs(2),
+        format!(
+            "{}Inside the{}",
+            line(glyph::VERTICAL),
+            line(glyph::VERTICAL)
+        )
+    );
+    assert_eq!(
+        glyphs(3),
+        format!(
+            "{}{}{}",
+            line(glyph::TEE_RIGHT),
+            edge.repeat(10),
+            line(glyph::TEE_LEFT)
+        )
+    );
+}